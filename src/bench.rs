@@ -0,0 +1,429 @@
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
+
+use glam::Vec3;
+
+use crate::camera::path::CameraPath;
+use crate::clock::SimulationClock;
+use crate::config::{
+    BENCH_FRAMES, BENCH_HEIGHT, BENCH_OUTPUT_FILE, BENCH_SEED, BENCH_TIMESTEP, BENCH_WARMUP_FRAMES,
+    BENCH_WIDTH, CAMERA_INITIAL_DISTANCE,
+};
+use crate::renderer::Renderer;
+use crate::renderer::profiler::{GpuFrameTimes, GpuPass};
+
+/// Percentiles written for every metric
+const PERCENTILES: [f32; 4] = [50.0, 90.0, 95.0, 99.0];
+
+/// What a benchmark run renders and where the results go
+#[derive(Clone, Debug)]
+pub struct BenchSettings {
+    /// Measured frames, after the warmup
+    pub frames: u32,
+    /// Frames rendered before measuring, so pipelines and caches are warm
+    pub warmup_frames: u32,
+    /// Scatters the grass the same way every run
+    pub seed: u64,
+    /// Simulation and camera time per frame in seconds
+    pub timestep: f32,
+    /// Renders offscreen without opening a window
+    pub headless: bool,
+    pub width: u32,
+    pub height: u32,
+    /// Results file, CSV when the extension is `.csv` and JSON otherwise
+    pub output: PathBuf,
+}
+
+impl Default for BenchSettings {
+    fn default() -> Self {
+        Self {
+            frames: BENCH_FRAMES,
+            warmup_frames: BENCH_WARMUP_FRAMES,
+            seed: BENCH_SEED,
+            timestep: BENCH_TIMESTEP,
+            headless: false,
+            width: BENCH_WIDTH,
+            height: BENCH_HEIGHT,
+            output: PathBuf::from(BENCH_OUTPUT_FILE),
+        }
+    }
+}
+
+/// Renders a fixed number of frames along a fixed camera path and measures them
+pub struct Benchmark {
+    settings: BenchSettings,
+    /// Frames rendered so far, warmup included
+    frame: u32,
+    last_frame_start: Option<Instant>,
+    /// Time spent in `Renderer::render`, in milliseconds
+    cpu_times: Vec<f32>,
+    /// Time from the start of one frame to the start of the next, in milliseconds
+    frame_times: Vec<f32>,
+    blade_counts: Vec<f32>,
+    gpu_times: Vec<GpuFrameTimes>,
+    /// Settings file applied to the renderer, written into the report
+    settings_file: Option<PathBuf>,
+}
+
+impl Benchmark {
+    pub fn new(settings: BenchSettings) -> Self {
+        Self {
+            settings,
+            frame: 0,
+            last_frame_start: None,
+            cpu_times: Vec::new(),
+            frame_times: Vec::new(),
+            blade_counts: Vec::new(),
+            gpu_times: Vec::new(),
+            settings_file: None,
+        }
+    }
+
+    pub fn settings(&self) -> &BenchSettings {
+        &self.settings
+    }
+
+    /// Fixes the timestep and camera of `renderer`, `settings_file` is the settings file applied to it
+    ///
+    /// Without `camera_path` the camera orbits the field once over the measured frames. The
+    /// camera waits on the start of the path during the warmup and stays on its end afterwards.
+    pub fn start(&mut self, renderer: &mut Renderer, camera_path: Option<CameraPath>, settings_file: Option<PathBuf>) {
+        *renderer.clock_mut() = SimulationClock::with_fixed_delta(self.settings.timestep);
+        renderer.set_vsync(false);
+        let camera_path = camera_path.unwrap_or_else(|| {
+            let duration = self.settings.frames as f32 * self.settings.timestep;
+            CameraPath::orbit(Vec3::ZERO, CAMERA_INITIAL_DISTANCE, 10.0, renderer.camera_mut().fovy, duration)
+        });
+        let warmup = self.settings.warmup_frames as f32 * self.settings.timestep;
+        renderer.hold_camera_path(camera_path, warmup);
+        self.settings_file = settings_file;
+        println!(
+            "Benchmark: {} frames after {} warmup frames, {}x{}, seed {}",
+            self.settings.frames,
+            self.settings.warmup_frames,
+            self.settings.width,
+            self.settings.height,
+            self.settings.seed,
+        );
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.frame >= self.settings.warmup_frames + self.settings.frames
+    }
+
+    /// Renders and measures the next frame
+    pub fn frame(&mut self, renderer: &mut Renderer) {
+        if self.is_finished() {
+            return;
+        }
+        if self.frame == self.settings.warmup_frames {
+            // warmup frames still in flight must not end up in the recording
+            renderer.wait_for_gpu();
+            if let Some(gpu_profiler) = renderer.gpu_profiler_mut() {
+                gpu_profiler.start_recording();
+            }
+        }
+        let measuring = self.frame >= self.settings.warmup_frames;
+        let start = Instant::now();
+        if let Some(last_frame_start) = self.last_frame_start {
+            self.frame_times.push(milliseconds_since(last_frame_start, start));
+        }
+
+        renderer.render();
+        let cpu_time = milliseconds_since(start, Instant::now());
+        // nothing else throttles a headless renderer, without waiting frames would pile up
+        if self.settings.headless {
+            renderer.wait_for_gpu();
+        }
+
+        if measuring {
+            self.cpu_times.push(cpu_time);
+            self.blade_counts.push(renderer.blade_count() as f32);
+            self.last_frame_start = Some(start);
+        }
+        self.frame += 1;
+
+        if self.is_finished() {
+            renderer.wait_for_gpu();
+            if let Some(last_frame_start) = self.last_frame_start.take() {
+                self.frame_times.push(milliseconds_since(last_frame_start, Instant::now()));
+            }
+            if let Some(gpu_profiler) = renderer.gpu_profiler_mut() {
+                self.gpu_times = gpu_profiler.stop_recording();
+            }
+        }
+    }
+
+    /// Summarises the measured frames
+    pub fn report(&self, renderer: &Renderer) -> BenchReport {
+        let mut metrics = vec![
+            Metric::new("cpu_frame_ms", &self.cpu_times),
+            Metric::new("frame_interval_ms", &self.frame_times),
+        ];
+        for (index, pass) in GpuPass::ALL.iter().enumerate() {
+            let samples = self.gpu_times.iter().filter_map(|frame| frame[index]).collect::<Vec<_>>();
+            metrics.push(Metric::new(&format!("gpu_{}_ms", format!("{:?}", pass).to_lowercase()), &samples));
+        }
+        metrics.push(Metric::new("blades", &self.blade_counts));
+
+        let size = renderer.size();
+        BenchReport {
+            adapter: renderer.adapter_name().to_string(),
+            headless: self.settings.headless,
+            width: size.width,
+            height: size.height,
+            sample_count: renderer.sample_count(),
+            frames: self.settings.frames,
+            gpu_frames: self.gpu_times.len(),
+            warmup_frames: self.settings.warmup_frames,
+            seed: self.settings.seed,
+            timestep: self.settings.timestep,
+            settings_file: self.settings_file.clone(),
+            metrics: metrics.into_iter().flatten().collect(),
+        }
+    }
+}
+
+fn milliseconds_since(earlier: Instant, later: Instant) -> f32 {
+    (later - earlier).as_secs_f32() * 1000.0
+}
+
+/// Distribution of one measured quantity over the benchmark frames
+#[derive(Clone, Debug)]
+pub struct Metric {
+    pub name: String,
+    pub samples: usize,
+    pub min: f32,
+    pub mean: f32,
+    pub max: f32,
+    /// Values at `PERCENTILES`
+    pub percentiles: [f32; PERCENTILES.len()],
+}
+
+impl Metric {
+    /// None without samples, e.g. for GPU passes the adapter cannot time
+    fn new(name: &str, samples: &[f32]) -> Option<Self> {
+        if samples.is_empty() {
+            return None;
+        }
+        let mut sorted = samples.to_vec();
+        sorted.sort_by(f32::total_cmp);
+        Some(Self {
+            name: name.to_string(),
+            samples: sorted.len(),
+            min: sorted[0],
+            mean: sorted.iter().sum::<f32>() / sorted.len() as f32,
+            max: sorted[sorted.len() - 1],
+            percentiles: PERCENTILES.map(|percentile| nearest_rank(&sorted, percentile)),
+        })
+    }
+}
+
+/// Smallest sample that is at least `percentile` percent of the samples
+fn nearest_rank(sorted: &[f32], percentile: f32) -> f32 {
+    let rank = (percentile / 100.0 * sorted.len() as f32).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Results of a benchmark run
+#[derive(Clone, Debug)]
+pub struct BenchReport {
+    pub adapter: String,
+    pub headless: bool,
+    pub width: u32,
+    pub height: u32,
+    pub sample_count: u32,
+    pub frames: u32,
+    /// Frames with GPU pass times, fewer than `frames` when the profiler dropped frames
+    /// because every readback was still busy, 0 when the adapter cannot time passes
+    pub gpu_frames: usize,
+    pub warmup_frames: u32,
+    pub seed: u64,
+    pub timestep: f32,
+    /// Settings file applied on top of the defaults, None when the defaults were measured
+    pub settings_file: Option<PathBuf>,
+    pub metrics: Vec<Metric>,
+}
+
+impl BenchReport {
+    /// Writes CSV when the extension of `path` is `.csv` and JSON otherwise
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let is_csv = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("csv"));
+        let text = if is_csv { self.to_csv() } else { self.to_json() };
+        std::fs::write(path, text)
+    }
+
+    pub fn to_json(&self) -> String {
+        let metrics = self
+            .metrics
+            .iter()
+            .map(|metric| {
+                let percentiles = PERCENTILES
+                    .iter()
+                    .zip(metric.percentiles)
+                    .map(|(percentile, value)| format!(", \"p{}\": {}", percentile, json_number(value)))
+                    .collect::<String>();
+                format!(
+                    "    {}: {{\"samples\": {}, \"min\": {}, \"mean\": {}, \"max\": {}{}}}",
+                    json_string(&metric.name),
+                    metric.samples,
+                    json_number(metric.min),
+                    json_number(metric.mean),
+                    json_number(metric.max),
+                    percentiles,
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",\n");
+        format!(
+            "{{\n  \"adapter\": {},\n  \"headless\": {},\n  \"width\": {},\n  \"height\": {},\n  \
+             \"msaa_samples\": {},\n  \"frames\": {},\n  \"gpu_frames\": {},\n  \"warmup_frames\": {},\n  \
+             \"seed\": {},\n  \
+             \"timestep\": {},\n  \"settings_file\": {},\n  \"metrics\": {{\n{}\n  }}\n}}\n",
+            json_string(&self.adapter),
+            self.headless,
+            self.width,
+            self.height,
+            self.sample_count,
+            self.frames,
+            self.gpu_frames,
+            self.warmup_frames,
+            self.seed,
+            json_number(self.timestep),
+            self.settings_file
+                .as_ref()
+                .map_or_else(|| "null".to_string(), |path| json_string(&path.display().to_string())),
+            metrics,
+        )
+    }
+
+    /// One row per metric, the run settings are left to the JSON output
+    pub fn to_csv(&self) -> String {
+        let percentiles = PERCENTILES.map(|percentile| format!(",p{}", percentile)).concat();
+        let mut text = format!("metric,samples,min,mean,max{}\n", percentiles);
+        for metric in &self.metrics {
+            let percentiles = metric.percentiles.map(|value| format!(",{}", value)).concat();
+            text.push_str(&format!(
+                "{},{},{},{},{}{}\n",
+                metric.name, metric.samples, metric.min, metric.mean, metric.max, percentiles,
+            ));
+        }
+        text
+    }
+
+    pub fn print(&self) {
+        println!("Benchmark results on {}:", self.adapter);
+        if self.gpu_frames < self.frames as usize {
+            println!("  GPU pass times from {} of {} frames", self.gpu_frames, self.frames);
+        }
+        for metric in &self.metrics {
+            let percentiles = PERCENTILES
+                .iter()
+                .zip(metric.percentiles)
+                .map(|(percentile, value)| format!("p{} {:.3}", percentile, value))
+                .collect::<Vec<_>>()
+                .join("  ");
+            println!("  {:<20} mean {:.3}  {}  max {:.3}", metric.name, metric.mean, percentiles, metric.max);
+        }
+    }
+}
+
+/// JSON has no NaN or infinity, e.g. the mean of a metric that overflowed
+fn json_number(value: f32) -> String {
+    if value.is_finite() {
+        value.to_string()
+    } else {
+        "null".to_string()
+    }
+}
+
+fn json_string(text: &str) -> String {
+    let mut escaped = String::from("\"");
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            c if c.is_control() => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn single_sample_is_every_percentile() {
+        let metric = Metric::new("one", &[4.0]).unwrap();
+        assert_eq!(metric.samples, 1);
+        assert_eq!((metric.min, metric.mean, metric.max), (4.0, 4.0, 4.0));
+        assert_eq!(metric.percentiles, [4.0; PERCENTILES.len()]);
+    }
+
+    #[test]
+    fn no_samples_give_no_metric() {
+        assert!(Metric::new("empty", &[]).is_none());
+    }
+
+    #[test]
+    fn nearest_rank_on_exact_ranks() {
+        // 1..=100, the p-th percentile is exactly p
+        let sorted = (1..=100).map(|i| i as f32).collect::<Vec<_>>();
+        assert_eq!(nearest_rank(&sorted, 50.0), 50.0);
+        assert_eq!(nearest_rank(&sorted, 90.0), 90.0);
+        assert_eq!(nearest_rank(&sorted, 99.0), 99.0);
+        assert_eq!(nearest_rank(&sorted, 100.0), 100.0);
+        assert_eq!(nearest_rank(&sorted, 0.0), 1.0);
+    }
+
+    #[test]
+    fn p99_of_few_samples_is_the_maximum() {
+        let metric = Metric::new("few", &[3.0, 1.0, 5.0, 2.0, 4.0]).unwrap();
+        assert_eq!(metric.percentiles, [3.0, 5.0, 5.0, 5.0]);
+        assert_eq!(metric.mean, 3.0);
+    }
+
+    #[test]
+    fn json_strings_are_escaped() {
+        assert_eq!(json_string("llvmpipe"), "\"llvmpipe\"");
+        assert_eq!(json_string("a \"b\" c\\d"), "\"a \\\"b\\\" c\\\\d\"");
+        assert_eq!(json_string("tab\tnew\n"), "\"tab\\u0009new\\u000a\"");
+    }
+
+    #[test]
+    fn report_json_escapes_the_adapter() {
+        let report = BenchReport {
+            adapter: "GPU \"X\"".to_string(),
+            headless: true,
+            width: 64,
+            height: 32,
+            sample_count: 1,
+            frames: 1,
+            gpu_frames: 0,
+            warmup_frames: 0,
+            seed: 7,
+            timestep: 0.5,
+            settings_file: None,
+            metrics: Metric::new("cpu_frame_ms", &[2.0]).into_iter().collect(),
+        };
+        let json = report.to_json();
+        assert!(json.contains("\"adapter\": \"GPU \\\"X\\\"\""));
+        assert!(json.contains("\"settings_file\": null"));
+        assert!(json.contains("\"cpu_frame_ms\": {\"samples\": 1, \"min\": 2, \"mean\": 2, \"max\": 2, \"p50\": 2"));
+        assert!(json.contains("\"frames\": 1,\n  \"gpu_frames\": 0,"));
+    }
+
+    #[test]
+    fn non_finite_json_numbers_are_null() {
+        assert_eq!(json_number(1.5), "1.5");
+        assert_eq!(json_number(f32::NAN), "null");
+        assert_eq!(json_number(f32::INFINITY), "null");
+        assert_eq!(json_number(f32::NEG_INFINITY), "null");
+    }
+}
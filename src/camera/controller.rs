@@ -1,66 +1,207 @@
 use glam::Vec3;
 use winit::event::{ElementState, MouseButton, MouseScrollDelta};
 use winit::dpi::PhysicalPosition;
+use winit::keyboard::KeyCode;
 use crate::config::{
-    CAMERA_MIN_DISTANCE, CAMERA_MAX_DISTANCE, 
-    CAMERA_ROTATION_SPEED, CAMERA_ZOOM_SPEED
+    CAMERA_MIN_DISTANCE, CAMERA_MAX_DISTANCE,
+    CAMERA_ROTATION_SPEED, CAMERA_ZOOM_SPEED,
+    CAMERA_FLY_SPEED, CAMERA_EYE_HEIGHT,
+    CAMERA_PAN_SPEED, CAMERA_SMOOTHING, CAMERA_INERTIA_DAMPING,
 };
 
+/// Speed multipliers while shift or control is held in fly mode
+const FAST_MULTIPLIER: f32 = 4.0;
+const SLOW_MULTIPLIER: f32 = 0.25;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum CameraMode {
+    /// Circles `target` with left drag, pans with right or middle drag and zooms with the wheel
+    #[default]
+    Orbit,
+    /// Moves with WASD, Q/E for down and up, looks around with left drag
+    Fly,
+}
+
+impl CameraMode {
+    pub fn next(self) -> Self {
+        match self {
+            CameraMode::Orbit => CameraMode::Fly,
+            CameraMode::Fly => CameraMode::Orbit,
+        }
+    }
+}
+
+/// Movement keys currently held down
+#[derive(Copy, Clone, Debug, Default)]
+struct MovementKeys {
+    forward: bool,
+    back: bool,
+    left: bool,
+    right: bool,
+    up: bool,
+    down: bool,
+    fast: bool,
+    slow: bool,
+}
+
+/// Orbit values the smoothed ones move towards
+#[derive(Copy, Clone, Debug)]
+struct OrbitGoal {
+    yaw: f32,
+    pitch: f32,
+    distance: f32,
+    target: Vec3,
+}
+
+/// Change of the orbit, either accumulated input or per second for the momentum
+#[derive(Copy, Clone, Debug, Default)]
+struct OrbitMotion {
+    yaw: f32,
+    pitch: f32,
+    pan: Vec3,
+}
+
+impl OrbitMotion {
+    fn scale(self, factor: f32) -> Self {
+        Self {
+            yaw: self.yaw * factor,
+            pitch: self.pitch * factor,
+            pan: self.pan * factor,
+        }
+    }
+}
+
 pub struct CameraController {
+    pub mode: CameraMode,
     is_left_pressed: bool,
+    is_pan_pressed: bool,
     last_mouse_pos: Option<PhysicalPosition<f64>>,
+    movement: MovementKeys,
+    goal: OrbitGoal,
+    /// Input since the last update
+    drag: OrbitMotion,
+    /// Carries the orbit on after a drag is released
+    velocity: OrbitMotion,
 
-    // orbit controls
+    // orbit controls, smoothed towards the input
     pub distance: f32,
     pub yaw: f32,   // horizontal angle (radians)
     pub pitch: f32,  // vertical angle (radians)
     pub target: Vec3,
 
+    // fly controls, the view direction comes from yaw and pitch as well
+    pub position: Vec3,
+    /// Metres per second before the speed modifiers
+    pub fly_speed: f32,
+    /// Keeps the eye `eye_height` above the ground in fly mode
+    pub ground_following: bool,
+    pub eye_height: f32,
+
     pub rotation_speed: f32,
     pub zoom_speed: f32,
+    pub pan_speed: f32,
+    /// How quickly the view catches up with the input, per second
+    pub smoothing: f32,
+    /// How quickly the momentum dies down after a drag, per second
+    pub inertia_damping: f32,
     pub min_distance: f32,
     pub max_distance: f32,
 }
 
 impl CameraController {
     pub fn new(distance: f32, target: Vec3) -> Self {
+        let goal = OrbitGoal {
+            yaw: 0.0,
+            pitch: 0.3,
+            distance,
+            target,
+        };
         Self {
+            mode: CameraMode::default(),
             is_left_pressed: false,
+            is_pan_pressed: false,
             last_mouse_pos: None,
+            movement: MovementKeys::default(),
+            goal,
+            drag: OrbitMotion::default(),
+            velocity: OrbitMotion::default(),
             distance,
-            yaw: 0.0,
-            pitch: 0.3,
+            yaw: goal.yaw,
+            pitch: goal.pitch,
             target,
+            position: target,
+            fly_speed: CAMERA_FLY_SPEED,
+            ground_following: false,
+            eye_height: CAMERA_EYE_HEIGHT,
             rotation_speed: CAMERA_ROTATION_SPEED,
             zoom_speed: CAMERA_ZOOM_SPEED,
+            pan_speed: CAMERA_PAN_SPEED,
+            smoothing: CAMERA_SMOOTHING,
+            inertia_damping: CAMERA_INERTIA_DAMPING,
             min_distance: CAMERA_MIN_DISTANCE,
             max_distance: CAMERA_MAX_DISTANCE,
         }
     }
 
-    pub fn process_mouse_button(&mut self, button: MouseButton, state: ElementState) {
-        if button == MouseButton::Left {
-            self.is_left_pressed = state == ElementState::Pressed;
-            if !self.is_left_pressed {
-                self.last_mouse_pos = None; // reset last position when released
+    /// Switches modes without moving the view
+    pub fn set_mode(&mut self, mode: CameraMode) {
+        if mode == self.mode {
+            return;
+        }
+        match mode {
+            CameraMode::Fly => self.position = self.orbit_position(),
+            CameraMode::Orbit => {
+                self.target = self.position + self.forward() * self.distance;
+                self.goal.target = self.target;
+                self.goal.distance = self.distance;
             }
         }
+        self.mode = mode;
+        self.movement = MovementKeys::default();
+        self.velocity = OrbitMotion::default();
+        self.drag = OrbitMotion::default();
+    }
+
+    pub fn process_mouse_button(&mut self, button: MouseButton, state: ElementState) {
+        let pressed = state == ElementState::Pressed;
+        match button {
+            MouseButton::Left => self.is_left_pressed = pressed,
+            MouseButton::Right | MouseButton::Middle => self.is_pan_pressed = pressed,
+            _ => {}
+        }
+        if pressed {
+            // grabbing the view stops the momentum
+            self.velocity = OrbitMotion::default();
+        }
     }
 
     pub fn process_mouse_move(&mut self, position: PhysicalPosition<f64>) {
-        if self.is_left_pressed {
-            if let Some(last_pos) = self.last_mouse_pos {
-                let delta_x = (position.x - last_pos.x) as f32;
-                let delta_y = (position.y - last_pos.y) as f32;
-
-                self.yaw -= delta_x * self.rotation_speed;
-                self.pitch += delta_y * self.rotation_speed;
-                // to avoid flipping
-                let pitch_limit = std::f32::consts::FRAC_PI_2 - 0.1;
-                self.pitch = self.pitch.clamp(-pitch_limit, pitch_limit);
+        if let Some(last_pos) = self.last_mouse_pos {
+            let delta_x = (position.x - last_pos.x) as f32;
+            let delta_y = (position.y - last_pos.y) as f32;
+
+            if self.is_left_pressed {
+                // the fly view looks back along the orbit offset, so the same
+                // update turns it the way the mouse moves. Only the orbit keeps
+                // momentum, `drag` is consumed by `update_orbit`
+                if self.mode == CameraMode::Orbit {
+                    self.drag.yaw -= delta_x * self.rotation_speed;
+                    self.drag.pitch += delta_y * self.rotation_speed;
+                }
+                self.goal.yaw -= delta_x * self.rotation_speed;
+                self.goal.pitch = clamp_pitch(self.goal.pitch + delta_y * self.rotation_speed);
+            }
+            if self.is_pan_pressed && self.mode == CameraMode::Orbit {
+                // drags the ground along with the cursor
+                let right = Vec3::new(self.yaw.cos(), 0.0, -self.yaw.sin());
+                let forward = Vec3::new(-self.yaw.sin(), 0.0, -self.yaw.cos());
+                let scale = self.pan_speed * self.goal.distance;
+                let pan = (-right * delta_x + forward * delta_y) * scale;
+                self.drag.pan += pan;
+                self.goal.target += pan;
             }
-            self.last_mouse_pos = Some(position);
         }
+        self.last_mouse_pos = Some(position);
     }
 
     pub fn process_scroll(&mut self, delta: MouseScrollDelta) {
@@ -68,16 +209,151 @@ impl CameraController {
             MouseScrollDelta::LineDelta(_, y) => y,
             MouseScrollDelta::PixelDelta(pos) => pos.y as f32,
         };
-        self.distance -= scroll_amount * self.zoom_speed;
-        self.distance = self.distance.clamp(self.min_distance, self.max_distance);
+        match self.mode {
+            CameraMode::Orbit => {
+                self.goal.distance -= scroll_amount * self.zoom_speed;
+                self.goal.distance = self.goal.distance.clamp(self.min_distance, self.max_distance);
+            }
+            CameraMode::Fly => {
+                self.fly_speed = (self.fly_speed * 1.2_f32.powf(scroll_amount.signum())).clamp(0.5, 100.0);
+            }
+        }
+    }
+
+    /// Tracks the fly mode movement keys, returns whether the key was used
+    pub fn process_keyboard(&mut self, key: KeyCode, state: ElementState) -> bool {
+        if self.mode != CameraMode::Fly {
+            return false;
+        }
+        let pressed = state == ElementState::Pressed;
+        let held = match key {
+            KeyCode::KeyW => &mut self.movement.forward,
+            KeyCode::KeyS => &mut self.movement.back,
+            KeyCode::KeyA => &mut self.movement.left,
+            KeyCode::KeyD => &mut self.movement.right,
+            KeyCode::KeyE => &mut self.movement.up,
+            KeyCode::KeyQ => &mut self.movement.down,
+            KeyCode::ShiftLeft | KeyCode::ShiftRight => &mut self.movement.fast,
+            KeyCode::ControlLeft | KeyCode::ControlRight => &mut self.movement.slow,
+            _ => return false,
+        };
+        *held = pressed;
+        true
+    }
+
+    /// Advances the smoothing, momentum and fly movement, `delta_time` is in real seconds
+    pub fn update(&mut self, delta_time: f32) {
+        if self.mode == CameraMode::Orbit {
+            self.update_orbit(delta_time);
+        }
+
+        // exponential smoothing, the same amount of catching up per second at any frame rate
+        let blend = 1.0 - (-self.smoothing * delta_time).exp();
+        self.yaw += (self.goal.yaw - self.yaw) * blend;
+        self.pitch += (self.goal.pitch - self.pitch) * blend;
+        self.distance += (self.goal.distance - self.distance) * blend;
+        self.target = self.target.lerp(self.goal.target, blend);
+
+        if self.mode == CameraMode::Fly {
+            self.update_fly(delta_time);
+        }
+    }
+
+    fn update_orbit(&mut self, delta_time: f32) {
+        if self.is_left_pressed || self.is_pan_pressed {
+            // remember how fast the drag was going for when it is released
+            if delta_time > 0.0 {
+                self.velocity = self.drag.scale(1.0 / delta_time);
+            }
+        } else {
+            let step = self.velocity.scale(delta_time);
+            self.goal.yaw += step.yaw;
+            self.goal.pitch = clamp_pitch(self.goal.pitch + step.pitch);
+            self.goal.target += step.pan;
+            self.velocity = self.velocity.scale((-self.inertia_damping * delta_time).exp());
+        }
+        self.drag = OrbitMotion::default();
+    }
+
+    fn update_fly(&mut self, delta_time: f32) {
+        let forward = if self.ground_following {
+            let flat = self.forward();
+            Vec3::new(flat.x, 0.0, flat.z).normalize_or_zero()
+        } else {
+            self.forward()
+        };
+        let right = self.forward().cross(Vec3::Y).normalize_or_zero();
+
+        let axis = |positive: bool, negative: bool| positive as i32 as f32 - negative as i32 as f32;
+        let mut direction = forward * axis(self.movement.forward, self.movement.back)
+            + right * axis(self.movement.right, self.movement.left);
+        if !self.ground_following {
+            direction += Vec3::Y * axis(self.movement.up, self.movement.down);
+        }
+
+        let mut speed = self.fly_speed;
+        if self.movement.fast {
+            speed *= FAST_MULTIPLIER;
+        }
+        if self.movement.slow {
+            speed *= SLOW_MULTIPLIER;
+        }
+        self.position += direction.normalize_or_zero() * speed * delta_time;
+
+        // the ground is the y = 0 plane
+        if self.ground_following {
+            self.position.y = self.eye_height;
+        }
+    }
+
+    /// Direction the camera looks in, shared by both modes
+    pub fn forward(&self) -> Vec3 {
+        -Vec3::new(
+            self.pitch.cos() * self.yaw.sin(),
+            self.pitch.sin(),
+            self.pitch.cos() * self.yaw.cos(),
+        )
     }
 
     pub fn calculate_position(&self) -> Vec3 {
+        match self.mode {
+            CameraMode::Orbit => self.orbit_position(),
+            CameraMode::Fly => self.position,
+        }
+    }
+
+    /// Point the camera looks at
+    pub fn look_target(&self) -> Vec3 {
+        match self.mode {
+            CameraMode::Orbit => self.target,
+            CameraMode::Fly => self.position + self.forward(),
+        }
+    }
+
+    /// Point on the ground in front of the camera, where local wind is spawned
+    pub fn focus_point(&self) -> Vec3 {
+        match self.mode {
+            CameraMode::Orbit => self.target,
+            CameraMode::Fly => {
+                let forward = self.forward();
+                let ahead = Vec3::new(forward.x, 0.0, forward.z).normalize_or_zero() * 5.0;
+                Vec3::new(self.position.x + ahead.x, 0.0, self.position.z + ahead.z)
+            }
+        }
+    }
+
+    fn orbit_position(&self) -> Vec3 {
         // Calculate position based on spherical coordinates
         let x = self.distance * self.pitch.cos() * self.yaw.sin();
         let y = self.distance * self.pitch.sin();
         let z = self.distance * self.pitch.cos() * self.yaw.cos();
-        
+
         self.target + Vec3::new(x, y, z)
     }
-}
\ No newline at end of file
+}
+
+/// Keeps the camera from flipping over the poles
+fn clamp_pitch(pitch: f32) -> f32 {
+    let pitch_limit = std::f32::consts::FRAC_PI_2 - 0.1;
+    pitch.clamp(-pitch_limit, pitch_limit)
+}
@@ -1,6 +1,17 @@
 pub mod controller;
+pub mod path;
 
-use glam::{Mat4, Vec3};
+use glam::{Mat4, Vec2, Vec3};
+
+/// Far plane of the orthographic projection, the perspective one reaches to infinity
+const ORTHOGRAPHIC_FAR: f32 = 1000.0;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Projection {
+    Perspective,
+    /// Parallel projection showing `height` world units from the bottom to the top of the view
+    Orthographic { height: f32 },
+}
 
 pub struct Camera {
     pub position: Vec3,
@@ -9,7 +20,9 @@ pub struct Camera {
     pub aspect: f32,
     pub fovy: f32,
     pub znear: f32,
-    pub zfar: f32,
+    pub projection: Projection,
+    /// Sub-pixel offset of the projection in NDC, set by TAA
+    pub jitter: Vec2,
 }
 
 impl Camera {
@@ -21,7 +34,8 @@ impl Camera {
             aspect,
             fovy: 45.0_f32.to_radians(),
             znear: 0.1,
-            zfar: 100.0,
+            projection: Projection::Perspective,
+            jitter: Vec2::ZERO,
         }
     }
 
@@ -29,12 +43,66 @@ impl Camera {
         self.position = position;
         self.target = target;
     }
-    
+
     pub fn build_view_projection_matrix(&self) -> Mat4 {
-        let view = Mat4::look_at_rh(self.position, self.target, self.up);
-        let proj = Mat4::perspective_rh(self.fovy, self.aspect, self.znear, self.zfar);
-        proj * view
+        // shifts every NDC position by `jitter`
+        let jitter = Mat4::from_translation(Vec3::new(self.jitter.x, self.jitter.y, 0.0));
+        jitter * self.build_unjittered_view_projection_matrix()
+    }
+
+    /// Same as `build_view_projection_matrix` without the TAA jitter, used for motion vectors
+    pub fn build_unjittered_view_projection_matrix(&self) -> Mat4 {
+        self.build_projection_matrix() * self.build_view_matrix()
+    }
+
+    /// Corners of the frustum cross-section `distance` in front of the camera,
+    /// counter-clockwise from the bottom left
+    pub fn frustum_corners(&self, distance: f32) -> [Vec3; 4] {
+        let camera_to_world = self.build_view_matrix().inverse();
+        let right = camera_to_world.x_axis.truncate();
+        let up = camera_to_world.y_axis.truncate();
+        let forward = -camera_to_world.z_axis.truncate();
+
+        let half_height = match self.projection {
+            Projection::Perspective => distance * (self.fovy * 0.5).tan(),
+            Projection::Orthographic { height } => height * 0.5,
+        };
+        let half_width = half_height * self.aspect;
+        let center = self.position + forward * distance;
+        [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+            .map(|(x, y)| center + right * (x * half_width) + up * (y * half_height))
+    }
+
+    fn build_view_matrix(&self) -> Mat4 {
+        // looking straight along the up vector leaves the roll undefined, keep -Z at the top
+        let forward = self.target - self.position;
+        let up = if forward.cross(self.up).length_squared() < 1e-6 * forward.length_squared() {
+            Vec3::NEG_Z
+        } else {
+            self.up
+        };
+        Mat4::look_at_rh(self.position, self.target, up)
     }
 
-    
-}
\ No newline at end of file
+    /// Reverse-Z, depth is 1 at `znear` and falls towards 0 at infinity or the orthographic far plane
+    fn build_projection_matrix(&self) -> Mat4 {
+        match self.projection {
+            Projection::Perspective => {
+                Mat4::perspective_infinite_reverse_rh(self.fovy, self.aspect, self.znear)
+            }
+            Projection::Orthographic { height } => {
+                let half_height = height * 0.5;
+                let half_width = half_height * self.aspect;
+                // swapped planes map the near plane to 1
+                Mat4::orthographic_rh(
+                    -half_width,
+                    half_width,
+                    -half_height,
+                    half_height,
+                    ORTHOGRAPHIC_FAR,
+                    self.znear,
+                )
+            }
+        }
+    }
+}
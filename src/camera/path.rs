@@ -0,0 +1,307 @@
+use std::io;
+use std::path::Path;
+
+use glam::Vec3;
+use super::Camera;
+
+/// Seconds between keyframes while recording
+const RECORD_INTERVAL: f32 = 0.1;
+/// Keyframes per circle of `CameraPath::orbit`, enough for the spline to stay round
+const ORBIT_KEYFRAMES: usize = 32;
+
+/// Camera state at one point of a path
+#[derive(Copy, Clone, Debug)]
+pub struct CameraKeyframe {
+    /// Seconds since the start of the path
+    pub time: f32,
+    pub position: Vec3,
+    pub target: Vec3,
+    /// Vertical field of view in radians
+    pub fovy: f32,
+}
+
+/// Recorded camera motion, played back along a Catmull-Rom spline through the keyframes
+///
+/// Stored as text with one keyframe per line:
+/// `time position.x position.y position.z target.x target.y target.z fovy`
+#[derive(Clone, Debug, Default)]
+pub struct CameraPath {
+    keyframes: Vec<CameraKeyframe>,
+}
+
+impl CameraPath {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let text = std::fs::read_to_string(path)?;
+        let mut keyframes = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let values = line
+                .split_whitespace()
+                .map(str::parse::<f32>)
+                .collect::<Result<Vec<_>, _>>()
+                .ok()
+                .filter(|values| values.len() == 8)
+                .ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("line {}: expected 8 numbers", number + 1),
+                    )
+                })?;
+            keyframes.push(CameraKeyframe {
+                time: values[0],
+                position: Vec3::new(values[1], values[2], values[3]),
+                target: Vec3::new(values[4], values[5], values[6]),
+                fovy: values[7],
+            });
+        }
+        if keyframes.is_empty() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "camera path has no keyframes"));
+        }
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+        Ok(Self { keyframes })
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let mut text = String::from("# time position.xyz target.xyz fovy\n");
+        for keyframe in &self.keyframes {
+            text.push_str(&format!(
+                "{} {} {} {} {} {} {} {}\n",
+                keyframe.time,
+                keyframe.position.x,
+                keyframe.position.y,
+                keyframe.position.z,
+                keyframe.target.x,
+                keyframe.target.y,
+                keyframe.target.z,
+                keyframe.fovy,
+            ));
+        }
+        std::fs::write(path, text)
+    }
+
+    /// One full circle of `radius` around `target` at `height` above it, taking `duration` seconds
+    pub fn orbit(target: Vec3, radius: f32, height: f32, fovy: f32, duration: f32) -> Self {
+        let keyframes = (0..=ORBIT_KEYFRAMES)
+            .map(|i| {
+                let t = i as f32 / ORBIT_KEYFRAMES as f32;
+                let angle = t * std::f32::consts::TAU;
+                CameraKeyframe {
+                    time: t * duration,
+                    position: target + Vec3::new(angle.sin() * radius, height, angle.cos() * radius),
+                    target,
+                    fovy,
+                }
+            })
+            .collect();
+        Self { keyframes }
+    }
+
+    pub fn duration(&self) -> f32 {
+        self.keyframes.last().map_or(0.0, |keyframe| keyframe.time)
+    }
+
+    /// Camera state at `time`, clamped to the ends of the path
+    pub fn sample(&self, time: f32) -> Option<CameraKeyframe> {
+        let last = self.keyframes.len().checked_sub(1)?;
+        let next = self.keyframes.partition_point(|keyframe| keyframe.time <= time);
+        if next == 0 {
+            return self.keyframes.first().copied();
+        }
+        if next > last {
+            return self.keyframes.last().copied();
+        }
+
+        let key = |index: usize| self.keyframes[index.min(last)];
+        let p0 = key(next.saturating_sub(2));
+        let p1 = key(next - 1);
+        let p2 = key(next);
+        let p3 = key(next + 1);
+        let span = p2.time - p1.time;
+        let t = if span > 0.0 { (time - p1.time) / span } else { 0.0 };
+
+        Some(CameraKeyframe {
+            time,
+            position: catmull_rom(p0.position, p1.position, p2.position, p3.position, t),
+            target: catmull_rom(p0.target, p1.target, p2.target, p3.target, t),
+            fovy: p1.fovy + (p2.fovy - p1.fovy) * t,
+        })
+    }
+}
+
+/// Samples the camera at a fixed interval into a `CameraPath`
+#[derive(Default)]
+pub struct PathRecorder {
+    path: CameraPath,
+    elapsed: f32,
+    since_keyframe: f32,
+}
+
+impl PathRecorder {
+    /// Adds a keyframe if enough time has passed, `delta_time` is in real seconds
+    pub fn record(&mut self, delta_time: f32, camera: &Camera) {
+        self.elapsed += delta_time;
+        self.since_keyframe += delta_time;
+        if !self.path.keyframes.is_empty() && self.since_keyframe < RECORD_INTERVAL {
+            return;
+        }
+        self.since_keyframe = 0.0;
+        self.path.keyframes.push(CameraKeyframe {
+            time: self.elapsed,
+            position: camera.position,
+            target: camera.target,
+            fovy: camera.fovy,
+        });
+    }
+
+    pub fn finish(self) -> CameraPath {
+        self.path
+    }
+}
+
+/// Moves through a `CameraPath` in real time
+pub struct PathPlayer {
+    path: CameraPath,
+    time: f32,
+    /// Stays on the last keyframe instead of finishing
+    hold: bool,
+}
+
+impl PathPlayer {
+    pub fn new(path: CameraPath) -> Self {
+        Self { path, time: 0.0, hold: false }
+    }
+
+    /// Waits `delay` seconds on the first keyframe and never finishes, the camera stays on the last keyframe
+    pub fn held(path: CameraPath, delay: f32) -> Self {
+        Self { path, time: -delay, hold: true }
+    }
+
+    /// Advances the playback and moves `camera` along the path
+    pub fn update(&mut self, delta_time: f32, camera: &mut Camera) {
+        if let Some(keyframe) = self.path.sample(self.time) {
+            camera.update_position(keyframe.position, keyframe.target);
+            camera.fovy = keyframe.fovy;
+        }
+        self.time += delta_time;
+    }
+
+    pub fn is_finished(&self) -> bool {
+        !self.hold && self.time > self.path.duration()
+    }
+}
+
+fn catmull_rom(p0: Vec3, p1: Vec3, p2: Vec3, p3: Vec3, t: f32) -> Vec3 {
+    let t2 = t * t;
+    let t3 = t2 * t;
+    0.5 * (2.0 * p1
+        + (p2 - p0) * t
+        + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+        + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keyframe(time: f32, x: f32) -> CameraKeyframe {
+        CameraKeyframe {
+            time,
+            position: Vec3::new(x, 2.0, 0.0),
+            target: Vec3::ZERO,
+            fovy: 1.0 + time,
+        }
+    }
+
+    fn straight_path() -> CameraPath {
+        CameraPath {
+            keyframes: (0..4).map(|i| keyframe(i as f32, i as f32 * 10.0)).collect(),
+        }
+    }
+
+    #[test]
+    fn sample_passes_through_keyframes() {
+        let path = straight_path();
+        for keyframe in &path.keyframes {
+            let sample = path.sample(keyframe.time).unwrap();
+            assert!(sample.position.abs_diff_eq(keyframe.position, 1e-5));
+            assert!((sample.fovy - keyframe.fovy).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn sample_interpolates_between_keyframes() {
+        // evenly spaced points on a line keep the spline on the line
+        let sample = straight_path().sample(1.5).unwrap();
+        assert!(sample.position.abs_diff_eq(Vec3::new(15.0, 2.0, 0.0), 1e-4));
+        assert!((sample.fovy - 2.5).abs() < 1e-5);
+        assert_eq!(sample.time, 1.5);
+    }
+
+    #[test]
+    fn sample_clamps_to_the_ends() {
+        let path = straight_path();
+        assert_eq!(path.sample(-1.0).unwrap().position, Vec3::new(0.0, 2.0, 0.0));
+        assert_eq!(path.sample(10.0).unwrap().position, Vec3::new(30.0, 2.0, 0.0));
+        assert_eq!(path.duration(), 3.0);
+        assert!(CameraPath::default().sample(0.0).is_none());
+    }
+
+    #[test]
+    fn orbit_stays_on_its_circle() {
+        let path = CameraPath::orbit(Vec3::ZERO, 20.0, 5.0, 1.0, 8.0);
+        assert_eq!(path.duration(), 8.0);
+        for i in 0..=16 {
+            let sample = path.sample(i as f32 * 0.5).unwrap();
+            let radius = Vec3::new(sample.position.x, 0.0, sample.position.z).length();
+            assert!((radius - 20.0).abs() < 0.05, "radius {} at {}", radius, sample.time);
+            assert_eq!(sample.position.y, 5.0);
+        }
+    }
+
+    #[test]
+    fn held_player_waits_and_stays_on_the_ends() {
+        let mut camera = Camera::new(Vec3::ONE, Vec3::ZERO, 1.0);
+        let mut player = PathPlayer::held(straight_path(), 1.0);
+        player.update(0.5, &mut camera);
+        assert_eq!(camera.position, Vec3::new(0.0, 2.0, 0.0));
+        player.update(0.5, &mut camera);
+        // the delay is over, the path starts now
+        player.update(0.5, &mut camera);
+        assert!(camera.position.abs_diff_eq(Vec3::new(0.0, 2.0, 0.0), 1e-5));
+        for _ in 0..20 {
+            player.update(0.5, &mut camera);
+        }
+        assert!(!player.is_finished());
+        assert_eq!(camera.position, Vec3::new(30.0, 2.0, 0.0));
+    }
+
+    #[test]
+    fn save_and_load_round_trip() {
+        let file = std::env::temp_dir().join(format!("camera_path_test_{}.txt", std::process::id()));
+        let path = straight_path();
+        path.save(&file).unwrap();
+        let loaded = CameraPath::load(&file);
+        std::fs::remove_file(&file).unwrap();
+
+        let loaded = loaded.unwrap();
+        assert_eq!(loaded.keyframes.len(), path.keyframes.len());
+        for (loaded, saved) in loaded.keyframes.iter().zip(&path.keyframes) {
+            assert_eq!(loaded.time, saved.time);
+            assert_eq!(loaded.position, saved.position);
+            assert_eq!(loaded.target, saved.target);
+            assert_eq!(loaded.fovy, saved.fovy);
+        }
+    }
+
+    #[test]
+    fn load_rejects_malformed_lines() {
+        let file = std::env::temp_dir().join(format!("camera_path_bad_{}.txt", std::process::id()));
+        std::fs::write(&file, "# comment\n0 1 2 3 4 5 6\n").unwrap();
+        let loaded = CameraPath::load(&file);
+        std::fs::remove_file(&file).unwrap();
+        assert_eq!(loaded.unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+    }
+}
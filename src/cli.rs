@@ -0,0 +1,238 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::bench::BenchSettings;
+use crate::config::{
+    CAMERA_PATH_FILE, DAY_CYCLE_DAY_OF_YEAR, DAY_CYCLE_LATITUDE, DAY_CYCLE_SPEED, MSAA_SAMPLE_COUNT,
+    SETTINGS_FILE,
+};
+use crate::lighting::day_cycle::DayCycle;
+use crate::renderer::debug_view::DebugView;
+use crate::renderer::shadow::ShadowSettings;
+use crate::renderer::taa::TaaSettings;
+use crate::renderer::tonemap::{TonemapSettings, Tonemapper};
+use crate::wind::flow_map::{FlowMapMode, FlowMapSettings};
+
+/// Command line options
+///
+/// `--flow-map <image>`             paint wind direction over the field
+/// `--flow-map-bias`                add the flow map to the wind instead of replacing it
+/// `--flow-map-scroll <u> <v>`      scroll the flow map, in UV units per second
+/// `--fixed-delta <seconds>`        advance the simulation by a fixed amount every frame
+/// `--time-of-day <hours>`          start the day cycle at the given local solar time
+/// `--latitude <degrees>`           latitude used by the day cycle
+/// `--day-of-year <day>`            date used by the day cycle, 0 is the first of January
+/// `--day-speed <multiplier>`       simulated seconds per real second of the day cycle
+/// `--tonemapper <clamp|aces|agx|reinhard>` curve used to map the HDR scene to the display, clamp keeps the pre-HDR look
+/// `--exposure <multiplier>`        scale the scene brightness before tonemapping
+/// `--auto-exposure`                adapt the exposure to the average scene luminance
+/// `--msaa <samples>`               requested MSAA level (1, 2, 4 or 8)
+/// `--taa`                          enable temporal anti-aliasing
+/// `--shadow-cascades <count>`      number of sun shadow cascades (1 to 4)
+/// `--shadow-resolution <pixels>`   width and height of each shadow cascade
+/// `--season <0..1>`                grass colour from spring green (0) to autumn straw (1)
+/// `--play-path <file>`             fly the camera along a recorded path, then exit
+/// `--record-path <file>`           where R saves a recorded camera path
+/// `--top-down`                     orthographic view straight down on the field
+/// `--minimap`                      show the top-down minimap in the corner
+/// `--debug-view <view>`            normals, wind-sway, blade-hash, species, height, overdraw, lod or clump
+/// `--wireframe`                    draw the triangle edges of the grass and ground
+/// `--debug-lines`                  draw the field bounds, camera frustum and wind arrows
+/// `--ui`                           show the parameter panels, U toggles them
+/// `--settings <file>`              settings loaded on startup and written by the save button,
+///                                  applied on top of the other options
+/// `--seed <number>`                scatter the grass the same way every run
+/// `--bench`                        render a fixed camera orbit, write frame and GPU pass times, then exit
+/// `--bench-frames <count>`         measured benchmark frames
+/// `--bench-warmup <count>`         frames rendered before measuring
+/// `--bench-size <width> <height>`  benchmark resolution in pixels
+/// `--bench-output <file>`          benchmark results, CSV for `.csv` files and JSON otherwise
+/// `--headless`                     benchmark offscreen without opening a window
+///
+/// Any of the day cycle options enables the day cycle and any of the benchmark
+/// options the benchmark. The benchmark uses `--seed` and `--fixed-delta` when given,
+/// follows `--play-path` instead of the orbit and only applies a settings file given with `--settings`.
+#[derive(Debug, Default)]
+pub struct Args {
+    pub flow_map: Option<PathBuf>,
+    pub flow_map_settings: FlowMapSettings,
+    pub fixed_delta: Option<f32>,
+    pub day_cycle: Option<DayCycle>,
+    pub tonemap: TonemapSettings,
+    pub msaa_samples: u32,
+    pub taa: TaaSettings,
+    pub shadow: ShadowSettings,
+    pub season: f32,
+    pub play_path: Option<PathBuf>,
+    pub record_path: PathBuf,
+    pub top_down: bool,
+    pub minimap: bool,
+    pub debug_view: DebugView,
+    pub wireframe: bool,
+    pub debug_lines: bool,
+    pub ui: bool,
+    pub settings_path: PathBuf,
+    /// `settings_path` was given with `--settings` rather than being the default
+    pub settings_given: bool,
+    pub seed: Option<u64>,
+    pub bench: Option<BenchSettings>,
+}
+
+impl Args {
+    pub fn parse() -> Self {
+        let mut args = Args {
+            msaa_samples: MSAA_SAMPLE_COUNT,
+            record_path: PathBuf::from(CAMERA_PATH_FILE),
+            settings_path: PathBuf::from(SETTINGS_FILE),
+            ..Default::default()
+        };
+        let mut flow_map_mode = FlowMapMode::Override;
+        let mut iter = std::env::args().skip(1);
+
+        while let Some(arg) = iter.next() {
+            match arg.as_str() {
+                "--flow-map" => args.flow_map = parse_value(&arg, iter.next()),
+                "--flow-map-bias" => flow_map_mode = FlowMapMode::Bias,
+                "--flow-map-scroll" => {
+                    let u = parse_value(&arg, iter.next()).unwrap_or(0.0);
+                    let v = parse_value(&arg, iter.next()).unwrap_or(0.0);
+                    args.flow_map_settings.scroll = [u, v];
+                }
+                "--fixed-delta" => args.fixed_delta = parse_value(&arg, iter.next()),
+                "--time-of-day" => {
+                    let value = parse_value(&arg, iter.next());
+                    args.day_cycle_mut().time_of_day = value.unwrap_or(12.0);
+                }
+                "--latitude" => {
+                    let value = parse_value(&arg, iter.next());
+                    args.day_cycle_mut().latitude = value.unwrap_or(DAY_CYCLE_LATITUDE);
+                }
+                "--day-of-year" => {
+                    let value = parse_value(&arg, iter.next());
+                    args.day_cycle_mut().day_of_year = value.unwrap_or(DAY_CYCLE_DAY_OF_YEAR);
+                }
+                "--day-speed" => {
+                    let value = parse_value(&arg, iter.next());
+                    args.day_cycle_mut().speed = value.unwrap_or(DAY_CYCLE_SPEED);
+                }
+                "--tonemapper" => match iter.next().as_deref() {
+                    Some("clamp") => args.tonemap.tonemapper = Tonemapper::Clamp,
+                    Some("aces") => args.tonemap.tonemapper = Tonemapper::Aces,
+                    Some("agx") => args.tonemap.tonemapper = Tonemapper::AgX,
+                    Some("reinhard") => args.tonemap.tonemapper = Tonemapper::Reinhard,
+                    other => log::warn!("Unknown tonemapper: {:?}", other),
+                },
+                "--exposure" => {
+                    let value = parse_value(&arg, iter.next());
+                    args.tonemap.exposure = value.unwrap_or(1.0);
+                }
+                "--auto-exposure" => args.tonemap.auto_exposure = true,
+                "--msaa" => {
+                    let value = parse_value(&arg, iter.next());
+                    args.msaa_samples = value.unwrap_or(MSAA_SAMPLE_COUNT);
+                }
+                "--taa" => args.taa.enabled = true,
+                "--shadow-cascades" => {
+                    if let Some(count) = parse_value(&arg, iter.next()) {
+                        args.shadow.cascade_count = count;
+                    }
+                }
+                "--shadow-resolution" => {
+                    if let Some(resolution) = parse_value(&arg, iter.next()) {
+                        args.shadow.resolution = resolution;
+                    }
+                }
+                "--season" => {
+                    let value = parse_value::<f32>(&arg, iter.next());
+                    args.season = value.unwrap_or(0.0).clamp(0.0, 1.0);
+                }
+                "--play-path" => args.play_path = parse_value(&arg, iter.next()),
+                "--record-path" => {
+                    if let Some(path) = parse_value(&arg, iter.next()) {
+                        args.record_path = path;
+                    }
+                }
+                "--top-down" => args.top_down = true,
+                "--minimap" => args.minimap = true,
+                "--debug-view" => match iter.next().as_deref() {
+                    Some("normals") => args.debug_view = DebugView::Normals,
+                    Some("wind-sway") => args.debug_view = DebugView::WindSway,
+                    Some("blade-hash") => args.debug_view = DebugView::BladeHash,
+                    Some("species") => args.debug_view = DebugView::Species,
+                    Some("height") => args.debug_view = DebugView::HeightFactor,
+                    Some("overdraw") => args.debug_view = DebugView::Overdraw,
+                    Some("lod") => args.debug_view = DebugView::LodLevel,
+                    Some("clump") => args.debug_view = DebugView::ClumpId,
+                    other => log::warn!("Unknown debug view: {:?}", other),
+                },
+                "--wireframe" => args.wireframe = true,
+                "--debug-lines" => args.debug_lines = true,
+                "--ui" => args.ui = true,
+                "--settings" => {
+                    if let Some(path) = parse_value(&arg, iter.next()) {
+                        args.settings_path = path;
+                        args.settings_given = true;
+                    }
+                }
+                "--seed" => args.seed = parse_value(&arg, iter.next()),
+                "--bench" => {
+                    args.bench_mut();
+                }
+                "--bench-frames" => {
+                    if let Some(frames) = parse_value(&arg, iter.next()) {
+                        args.bench_mut().frames = frames;
+                    }
+                }
+                "--bench-warmup" => {
+                    if let Some(frames) = parse_value(&arg, iter.next()) {
+                        args.bench_mut().warmup_frames = frames;
+                    }
+                }
+                "--bench-size" => {
+                    let width = parse_value::<u32>(&arg, iter.next());
+                    let height = parse_value::<u32>(&arg, iter.next());
+                    let bench = args.bench_mut();
+                    bench.width = width.unwrap_or(bench.width).max(1);
+                    bench.height = height.unwrap_or(bench.height).max(1);
+                }
+                "--bench-output" => {
+                    if let Some(path) = parse_value(&arg, iter.next()) {
+                        args.bench_mut().output = path;
+                    }
+                }
+                "--headless" => args.bench_mut().headless = true,
+                other => log::warn!("Ignoring unknown argument: {}", other),
+            }
+        }
+
+        if args.flow_map.is_some() {
+            args.flow_map_settings.mode = flow_map_mode;
+        }
+        if let Some(bench) = &mut args.bench {
+            bench.seed = args.seed.unwrap_or(bench.seed);
+            bench.timestep = args.fixed_delta.unwrap_or(bench.timestep);
+        }
+        args
+    }
+
+    fn bench_mut(&mut self) -> &mut BenchSettings {
+        self.bench.get_or_insert_with(BenchSettings::default)
+    }
+
+    fn day_cycle_mut(&mut self) -> &mut DayCycle {
+        self.day_cycle.get_or_insert_with(DayCycle::default)
+    }
+}
+
+/// Parses the value given to `option`, None with a warning when it is missing or invalid
+fn parse_value<T: FromStr>(option: &str, value: Option<String>) -> Option<T> {
+    let Some(value) = value else {
+        log::warn!("Missing value for {}", option);
+        return None;
+    };
+    let parsed = value.parse().ok();
+    if parsed.is_none() {
+        log::warn!("Ignoring invalid value {:?} for {}", value, option);
+    }
+    parsed
+}
@@ -0,0 +1,181 @@
+use std::time::Instant;
+
+use crate::config::SIMULATION_TIMESTEP;
+
+/// Drives simulation time separately from wall-clock time
+///
+/// Supports pausing, slow motion / fast forward and stepping one fixed
+/// timestep at a time while paused. A fixed delta can be injected so that
+/// every tick advances by the same amount regardless of frame rate.
+pub struct SimulationClock {
+    last_tick: Instant,
+    time: f64,
+    time_scale: f32,
+    paused: bool,
+    pending_steps: u32,
+    fixed_delta: Option<f32>,
+    real_delta: f32,
+}
+
+impl SimulationClock {
+    pub fn new() -> Self {
+        Self {
+            last_tick: Instant::now(),
+            time: 0.0,
+            time_scale: 1.0,
+            paused: false,
+            pending_steps: 0,
+            fixed_delta: None,
+            real_delta: 0.0,
+        }
+    }
+
+    /// Clock that advances by `delta` seconds every tick, ignoring wall-clock time
+    pub fn with_fixed_delta(delta: f32) -> Self {
+        Self {
+            fixed_delta: Some(delta),
+            ..Self::new()
+        }
+    }
+
+    /// Advances the clock, call once per frame. Returns the simulation delta.
+    pub fn tick(&mut self) -> f32 {
+        let now = Instant::now();
+        let real_delta = match self.fixed_delta {
+            Some(delta) => delta,
+            None => (now - self.last_tick).as_secs_f32(),
+        };
+        self.last_tick = now;
+        self.real_delta = real_delta;
+
+        let delta = if !self.paused {
+            real_delta * self.time_scale
+        } else if self.pending_steps > 0 {
+            self.pending_steps -= 1;
+            SIMULATION_TIMESTEP
+        } else {
+            0.0
+        };
+
+        self.time += delta as f64;
+        delta
+    }
+
+    /// Unscaled delta of the last tick, keeps running while paused
+    pub fn real_delta(&self) -> f32 {
+        self.real_delta
+    }
+
+    /// Simulation time in seconds
+    pub fn time(&self) -> f32 {
+        self.time as f32
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.pending_steps = 0;
+    }
+
+    pub fn toggle_pause(&mut self) {
+        if self.paused {
+            self.resume();
+        } else {
+            self.pause();
+        }
+    }
+
+    /// Advances one fixed timestep on the next tick, pauses the clock if running
+    pub fn step(&mut self) {
+        self.paused = true;
+        self.pending_steps += 1;
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    pub fn set_time_scale(&mut self, scale: f32) {
+        self.time_scale = scale.max(0.0);
+    }
+}
+
+impl Default for SimulationClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fixed_delta_ignores_wall_clock() {
+        let mut clock = SimulationClock::with_fixed_delta(0.25);
+        assert_eq!(clock.tick(), 0.25);
+        assert_eq!(clock.tick(), 0.25);
+        assert_eq!(clock.real_delta(), 0.25);
+        assert_eq!(clock.time(), 0.5);
+    }
+
+    #[test]
+    fn paused_clock_stands_still() {
+        let mut clock = SimulationClock::with_fixed_delta(0.25);
+        clock.pause();
+        assert!(clock.is_paused());
+        assert_eq!(clock.tick(), 0.0);
+        assert_eq!(clock.time(), 0.0);
+        // the real delta keeps running for the camera
+        assert_eq!(clock.real_delta(), 0.25);
+
+        clock.toggle_pause();
+        assert!(!clock.is_paused());
+        assert_eq!(clock.tick(), 0.25);
+    }
+
+    #[test]
+    fn step_advances_one_timestep_while_paused() {
+        let mut clock = SimulationClock::with_fixed_delta(0.25);
+        clock.step();
+        assert!(clock.is_paused());
+        assert_eq!(clock.tick(), SIMULATION_TIMESTEP);
+        assert_eq!(clock.tick(), 0.0);
+
+        clock.step();
+        clock.step();
+        assert_eq!(clock.tick(), SIMULATION_TIMESTEP);
+        assert_eq!(clock.tick(), SIMULATION_TIMESTEP);
+        assert_eq!(clock.tick(), 0.0);
+    }
+
+    #[test]
+    fn resume_drops_pending_steps() {
+        let mut clock = SimulationClock::with_fixed_delta(0.25);
+        clock.step();
+        clock.resume();
+        assert_eq!(clock.tick(), 0.25);
+        clock.pause();
+        assert_eq!(clock.tick(), 0.0);
+    }
+
+    #[test]
+    fn time_scale_scales_simulation_but_not_real_delta() {
+        let mut clock = SimulationClock::with_fixed_delta(0.25);
+        clock.set_time_scale(2.0);
+        assert_eq!(clock.tick(), 0.5);
+        assert_eq!(clock.real_delta(), 0.25);
+
+        clock.set_time_scale(-1.0);
+        assert_eq!(clock.time_scale(), 0.0);
+        assert_eq!(clock.tick(), 0.0);
+        assert_eq!(clock.time(), 0.5);
+    }
+}
@@ -1,8 +1,9 @@
-use std::f32::consts::PI;
-
 /// Number of grass blade instances to render
 pub const GRASS_COUNT: usize = 64 * 512;
 
+/// Half the side length of the square grass field, centred on the origin
+pub const FIELD_HALF_SIZE: f32 = 25.0;
+
 /// Number of segments per grass blade (more = smoother bending)
 pub const BLADE_SEGMENTS: u32 = 6;
 
@@ -18,12 +19,79 @@ pub const WIND_STRENGTH: f32 = 0.65;
 /// Wind direction in radians
 pub const WIND_ANGLE: f32 = 0.0;
 
+/// Fixed timestep of the blade simulation in seconds
+pub const SIMULATION_TIMESTEP: f32 = 1.0 / 120.0;
+
+/// Upper bound on simulation steps per frame at normal speed (avoids spiralling after a hitch),
+/// multiplied by the clock's time scale when it runs faster
+pub const MAX_SIMULATION_STEPS: u32 = 8;
+
+/// Spring stiffness pulling blades back to upright
+pub const BLADE_STIFFNESS: f32 = 40.0;
+
+/// Velocity damping of the blade spring
+pub const BLADE_DAMPING: f32 = 4.0;
+
+/// How strongly gravity pulls an already leaning blade further over
+pub const BLADE_GRAVITY: f32 = 2.0;
+
+/// Direction towards the sun (does not need to be normalized)
+pub const SUN_DIRECTION: [f32; 3] = [-1.0, 0.5, 1.0];
+
+/// Day cycle settings, the speed is in simulated seconds per real second
+pub const DAY_CYCLE_LATITUDE: f32 = 45.0;
+pub const DAY_CYCLE_DAY_OF_YEAR: u32 = 172;
+pub const DAY_CYCLE_SPEED: f32 = 600.0;
+
+/// Height fog settings
+pub const FOG_DENSITY: f32 = 0.006;
+pub const FOG_HEIGHT_FALLOFF: f32 = 0.1;
+
+/// MSAA samples requested for the scene, lowered to what the adapter supports
+pub const MSAA_SAMPLE_COUNT: u32 = 4;
+
+/// Camera distances where grass blades drop to the next LOD level
+pub const GRASS_LOD_DISTANCES: [f32; 2] = [20.0, 50.0];
+
+/// Average width of a blade clump, blades are grouped by the nearest jittered cell centre
+pub const GRASS_CLUMP_SIZE: f32 = 2.0;
+
+/// Shadow settings
+pub const SHADOW_CASCADE_COUNT: u32 = 3;
+pub const SHADOW_MAP_RESOLUTION: u32 = 2048;
+pub const SHADOW_DISTANCE: f32 = 80.0;
+/// Blend between uniform (0) and logarithmic (1) cascade splits
+pub const SHADOW_SPLIT_LAMBDA: f32 = 0.6;
+
 /// Camera settings
 pub const CAMERA_INITIAL_DISTANCE: f32 = 25.0;
 pub const CAMERA_MIN_DISTANCE: f32 = 5.0;
-pub const CAMERA_MAX_DISTANCE: f32 = 100.0;
+pub const CAMERA_MAX_DISTANCE: f32 = 200.0;
 pub const CAMERA_ROTATION_SPEED: f32 = 0.005;
 pub const CAMERA_ZOOM_SPEED: f32 = 2.0;
+/// Pan distance per pixel of drag, scaled by the orbit distance
+pub const CAMERA_PAN_SPEED: f32 = 0.0015;
+/// How quickly the view catches up with the input, per second
+pub const CAMERA_SMOOTHING: f32 = 12.0;
+/// How quickly the orbit momentum dies down after releasing a drag, per second
+pub const CAMERA_INERTIA_DAMPING: f32 = 4.0;
+/// Fly mode speed in metres per second and height above the ground when following it
+pub const CAMERA_FLY_SPEED: f32 = 8.0;
+pub const CAMERA_EYE_HEIGHT: f32 = 1.7;
+/// Default file for recorded camera paths
+pub const CAMERA_PATH_FILE: &str = "camera_path.txt";
+
+/// Default file the settings panel saves to, loaded on startup if present
+pub const SETTINGS_FILE: &str = "grass_settings.txt";
+
+/// Benchmark defaults, the timestep is the simulated and camera time per frame
+pub const BENCH_FRAMES: u32 = 600;
+pub const BENCH_WARMUP_FRAMES: u32 = 60;
+pub const BENCH_SEED: u64 = 1;
+pub const BENCH_TIMESTEP: f32 = 1.0 / 60.0;
+pub const BENCH_WIDTH: u32 = 1280;
+pub const BENCH_HEIGHT: u32 = 720;
+pub const BENCH_OUTPUT_FILE: &str = "bench_results.json";
 
 /// Sky color
 pub const SKY_COLOR: wgpu::Color = wgpu::Color {
@@ -0,0 +1,31 @@
+use crate::config::{BLADE_DAMPING, BLADE_GRAVITY, BLADE_HEIGHT, BLADE_STIFFNESS};
+
+/// Shape and spring of the blades, applied by the compute shader every step
+#[derive(Copy, Clone, Debug)]
+pub struct BladeSettings {
+    /// Average blade height, individual blades vary by 20% either way
+    pub height: f32,
+    /// Width relative to the blade mesh
+    pub width: f32,
+    /// How much the blade curves as the wind bends it, 0 keeps it straight
+    pub bend: f32,
+    /// Spring stiffness pulling blades back to upright
+    pub stiffness: f32,
+    /// Velocity damping of the spring
+    pub damping: f32,
+    /// How strongly gravity pulls an already leaning blade further over
+    pub gravity: f32,
+}
+
+impl Default for BladeSettings {
+    fn default() -> Self {
+        Self {
+            height: BLADE_HEIGHT,
+            width: 1.0,
+            bend: 1.0,
+            stiffness: BLADE_STIFFNESS,
+            damping: BLADE_DAMPING,
+            gravity: BLADE_GRAVITY,
+        }
+    }
+}
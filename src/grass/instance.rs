@@ -1,3 +1,7 @@
+use rand::Rng;
+
+use crate::config::FIELD_HALF_SIZE;
+
 #[repr(C)]
 #[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
 pub struct GrassInstance {
@@ -9,17 +13,26 @@ pub struct GrassInstance {
     pub tilt: f32,
     pub facing: [f32; 2],
     pub blade_hash: f32,
-    pub _padding: f32,  
+    pub sway_angle: f32,
+}
+
+/// Per-blade simulation state, ping-ponged between frames by the compute pass
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct BladeState {
+    pub tip_offset: [f32; 2],
+    pub velocity: [f32; 2],
 }
 
 impl GrassInstance {
-    pub fn new() -> Self {
+    /// Blade at a random spot of the field
+    pub fn new(rng: &mut impl Rng) -> Self {
         Self {
             // should maybe be done on the GPU
             position: [
-                rand::random::<f32>() * 50.0 - 25.0,
+                (rng.random::<f32>() * 2.0 - 1.0) * FIELD_HALF_SIZE,
                 0.0,
-                rand::random::<f32>() * 50.0 - 25.0,
+                (rng.random::<f32>() * 2.0 - 1.0) * FIELD_HALF_SIZE,
             ],
             wind_sway: 0.0,  
             height: 1.0,     
@@ -28,7 +41,7 @@ impl GrassInstance {
             tilt: 0.0,       
             facing: [0.0, 0.0], 
             blade_hash: 0.0, 
-            _padding: 0.0,
+            sway_angle: 0.0,
         }
     }
 
@@ -77,6 +90,31 @@ impl GrassInstance {
                     shader_location: 8,
                     format: wgpu::VertexFormat::Float32,
                 },
+                wgpu::VertexAttribute {
+                    offset: 44,
+                    shader_location: 9,
+                    format: wgpu::VertexFormat::Float32,
+                },
+            ],
+        }
+    }
+
+    /// Wind state of the previous frame, read from a copy of the instance buffer for motion vectors
+    pub fn previous_vertex_buffer_layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<GrassInstance>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Instance,
+            attributes: &[
+                wgpu::VertexAttribute {
+                    offset: 12,
+                    shader_location: 10,
+                    format: wgpu::VertexFormat::Float32,
+                },
+                wgpu::VertexAttribute {
+                    offset: 44,
+                    shader_location: 11,
+                    format: wgpu::VertexFormat::Float32,
+                },
             ],
         }
     }
@@ -5,6 +5,8 @@ pub struct GrassMesh {
     vertex_buffer: wgpu::Buffer,
     index_buffer: wgpu::Buffer,
     num_indices: u32,
+    /// Triangle corners in draw order, for the barycentric wireframe
+    wireframe_vertex_buffer: wgpu::Buffer,
 }
 
 impl GrassMesh {
@@ -31,6 +33,7 @@ impl GrassMesh {
         }
         
         for i in 0..BLADE_SEGMENTS {
+            #[allow(clippy::unnecessary_cast)]
             let base = (i * 2) as u32;
             
             indices.push(base);
@@ -54,10 +57,21 @@ impl GrassMesh {
             usage: wgpu::BufferUsages::INDEX,
         });
 
+        let wireframe_vertices = indices
+            .iter()
+            .flat_map(|&index| vertices[index as usize * 3..index as usize * 3 + 3].to_vec())
+            .collect::<Vec<f32>>();
+        let wireframe_vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Grass Wireframe Vertex Buffer"),
+            contents: bytemuck::cast_slice(&wireframe_vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
         Self {
             vertex_buffer,
             index_buffer,
             num_indices: indices.len() as u32,
+            wireframe_vertex_buffer,
         }
     }
 
@@ -72,4 +86,9 @@ impl GrassMesh {
     pub fn num_indices(&self) -> u32 {
         self.num_indices
     }
+
+    /// Holds `num_indices` vertices, drawn without the index buffer
+    pub fn wireframe_vertex_buffer(&self) -> &wgpu::Buffer {
+        &self.wireframe_vertex_buffer
+    }
 }
\ No newline at end of file
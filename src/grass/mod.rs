@@ -1,28 +1,48 @@
+pub mod blade;
 pub mod mesh;
 pub mod instance;
+pub mod species;
 
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 use wgpu::util::DeviceExt;
 
 pub struct Grass {
     instances: Vec<instance::GrassInstance>,
     instance_buffer: wgpu::Buffer,
+    /// Instance buffer as it was before this frame's simulation steps
+    previous_instance_buffer: wgpu::Buffer,
 }
 
 impl Grass {
-    pub fn new(device: &wgpu::Device, count: usize) -> Self {
+    /// Scatters `count` blades, the same `seed` always gives the same field
+    pub fn new(device: &wgpu::Device, count: usize, seed: Option<u64>) -> Self {
+        let mut rng = match seed {
+            Some(seed) => StdRng::seed_from_u64(seed),
+            None => StdRng::from_os_rng(),
+        };
         let instances = (0..count)
-            .map(|_| instance::GrassInstance::new())
+            .map(|_| instance::GrassInstance::new(&mut rng))
             .collect::<Vec<_>>();
 
         let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Instance Buffer"),
             contents: bytemuck::cast_slice(&instances),
-            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            usage: wgpu::BufferUsages::VERTEX
+                | wgpu::BufferUsages::STORAGE
+                | wgpu::BufferUsages::COPY_DST
+                | wgpu::BufferUsages::COPY_SRC,
+        });
+        let previous_instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Previous Instance Buffer"),
+            contents: bytemuck::cast_slice(&instances),
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
         });
 
         Grass {
             instances,
             instance_buffer,
+            previous_instance_buffer,
         }
     }
 
@@ -30,6 +50,21 @@ impl Grass {
         &self.instance_buffer
     }
     
+    pub fn get_previous_instance_buffer(&self) -> &wgpu::Buffer {
+        &self.previous_instance_buffer
+    }
+
+    /// Keeps the current wind state around before the simulation advances it
+    pub fn store_previous(&self, encoder: &mut wgpu::CommandEncoder) {
+        encoder.copy_buffer_to_buffer(
+            &self.instance_buffer,
+            0,
+            &self.previous_instance_buffer,
+            0,
+            self.instance_buffer.size(),
+        );
+    }
+
     pub fn instance_count(&self) -> u32 {
         self.instances.len() as u32
     }
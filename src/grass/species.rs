@@ -0,0 +1,127 @@
+use bytemuck::Zeroable;
+use glam::Vec3;
+
+/// Size of the species array in grass.wgsl
+pub const MAX_GRASS_SPECIES: usize = 4;
+
+/// Material of one kind of grass in the field
+#[derive(Copy, Clone, Debug)]
+pub struct GrassSpecies {
+    /// Colour at the root of the blade
+    pub base_color: Vec3,
+    /// Colour at the tip, blended from `base_color` along the blade
+    pub tip_color: Vec3,
+    /// Blade thickness at the base, thicker blades let less light through
+    pub thickness: f32,
+    /// Colour of sunlight after passing through a blade
+    pub transmission_color: Vec3,
+}
+
+/// Species the field is made of and the colour variation across it
+#[derive(Clone, Debug)]
+pub struct GrassMaterial {
+    /// Blades pick one at random, neighbouring blades tend to share a species
+    pub species: Vec<GrassSpecies>,
+    /// 0 is spring green, 1 is autumn straw
+    pub season: f32,
+    /// How much the large scale noise shifts the colour, 0 disables it
+    pub variation_strength: f32,
+    /// Frequency of the large scale variation, per metre
+    pub variation_scale: f32,
+    /// Colour of dead grass in the dry patches
+    pub dry_color: Vec3,
+    /// Share of the field covered by dry patches before the season adds to it
+    pub dry_patch_coverage: f32,
+    /// Frequency of the dry patches, per metre
+    pub dry_patch_scale: f32,
+    /// Colour the whole field turns towards in autumn
+    pub autumn_color: Vec3,
+}
+
+impl Default for GrassMaterial {
+    fn default() -> Self {
+        Self {
+            species: vec![
+                // meadow grass
+                GrassSpecies {
+                    base_color: Vec3::new(0.15, 0.45, 0.12),
+                    tip_color: Vec3::new(0.35, 0.85, 0.3),
+                    thickness: 0.3,
+                    transmission_color: Vec3::new(0.6, 0.9, 0.2),
+                },
+                // fine fescue
+                GrassSpecies {
+                    base_color: Vec3::new(0.2, 0.5, 0.18),
+                    tip_color: Vec3::new(0.5, 0.9, 0.4),
+                    thickness: 0.15,
+                    transmission_color: Vec3::new(0.75, 0.95, 0.3),
+                },
+                // broad leaved grass
+                GrassSpecies {
+                    base_color: Vec3::new(0.1, 0.35, 0.08),
+                    tip_color: Vec3::new(0.3, 0.72, 0.2),
+                    thickness: 0.6,
+                    transmission_color: Vec3::new(0.45, 0.75, 0.15),
+                },
+            ],
+            season: 0.0,
+            variation_strength: 0.8,
+            variation_scale: 0.04,
+            dry_color: Vec3::new(0.55, 0.45, 0.25),
+            dry_patch_coverage: 0.15,
+            dry_patch_scale: 0.12,
+            autumn_color: Vec3::new(0.8, 0.68, 0.38),
+        }
+    }
+}
+
+impl GrassMaterial {
+    /// Species past `MAX_GRASS_SPECIES` are ignored
+    pub fn to_uniforms(&self) -> MaterialUniforms {
+        let mut uniforms = MaterialUniforms {
+            species: [SpeciesUniforms::zeroed(); MAX_GRASS_SPECIES],
+            species_count: self.species.len().clamp(1, MAX_GRASS_SPECIES) as u32,
+            season: self.season.clamp(0.0, 1.0),
+            variation_strength: self.variation_strength,
+            variation_scale: self.variation_scale,
+            dry_color: self.dry_color.to_array(),
+            dry_patch_coverage: self.dry_patch_coverage,
+            autumn_color: self.autumn_color.to_array(),
+            dry_patch_scale: self.dry_patch_scale,
+        };
+        for (uniform, species) in uniforms.species.iter_mut().zip(&self.species) {
+            uniform.transmission_color = species.transmission_color.to_array();
+            uniform.thickness = species.thickness;
+            uniform.base_color = species.base_color.to_array();
+            uniform.tip_color = species.tip_color.to_array();
+        }
+        uniforms
+    }
+}
+
+/// Matches `Species` in grass.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct SpeciesUniforms {
+    pub transmission_color: [f32; 3],
+    pub thickness: f32,
+    pub base_color: [f32; 3],
+    pub _padding1: f32,
+    pub tip_color: [f32; 3],
+    pub _padding2: f32,
+}
+
+/// Matches `Material` in grass.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct MaterialUniforms {
+    pub species: [SpeciesUniforms; MAX_GRASS_SPECIES],
+    pub species_count: u32,
+    pub season: f32,
+    pub variation_strength: f32,
+    pub variation_scale: f32,
+    pub dry_color: [f32; 3],
+    pub dry_patch_coverage: f32,
+    pub autumn_color: [f32; 3],
+    pub dry_patch_scale: f32,
+}
@@ -0,0 +1,149 @@
+use std::f32::consts::TAU;
+
+use glam::Vec3;
+use super::Lighting;
+use crate::config::{DAY_CYCLE_DAY_OF_YEAR, DAY_CYCLE_LATITUDE, DAY_CYCLE_SPEED};
+
+const HOURS_PER_DAY: f32 = 24.0;
+const DAYS_PER_YEAR: u32 = 365;
+/// Tilt of the earth's axis in degrees
+const AXIAL_TILT: f32 = 23.44;
+
+const NOON_SUN_COLOR: Vec3 = Vec3::new(1.0, 1.0, 0.9);
+const HORIZON_SUN_COLOR: Vec3 = Vec3::new(1.0, 0.45, 0.2);
+const MOON_COLOR: Vec3 = Vec3::new(0.55, 0.65, 1.0);
+const MOON_INTENSITY: f32 = 0.15;
+
+/// Ambient and sky colours at night, during twilight and during the day
+const NIGHT: Palette = Palette {
+    sky: Vec3::new(0.05, 0.07, 0.15),
+    ground: Vec3::new(0.01, 0.01, 0.05),
+    horizon: Vec3::new(0.01, 0.015, 0.04),
+    zenith: Vec3::new(0.0, 0.0, 0.01),
+    ambient_intensity: 0.3,
+};
+const TWILIGHT: Palette = Palette {
+    sky: Vec3::new(0.9, 0.55, 0.45),
+    ground: Vec3::new(0.05, 0.03, 0.12),
+    horizon: Vec3::new(0.9, 0.5, 0.3),
+    zenith: Vec3::new(0.3, 0.3, 0.5),
+    ambient_intensity: 0.6,
+};
+
+#[derive(Copy, Clone, Debug)]
+struct Palette {
+    sky: Vec3,
+    ground: Vec3,
+    horizon: Vec3,
+    zenith: Vec3,
+    ambient_intensity: f32,
+}
+
+impl Palette {
+    fn day() -> Self {
+        let lighting = Lighting::default();
+        Self {
+            sky: lighting.sky_color,
+            ground: lighting.ground_color,
+            horizon: lighting.horizon_color,
+            zenith: lighting.zenith_color,
+            ambient_intensity: lighting.ambient_intensity,
+        }
+    }
+
+    fn lerp(self, other: Self, t: f32) -> Self {
+        Self {
+            sky: self.sky.lerp(other.sky, t),
+            ground: self.ground.lerp(other.ground, t),
+            horizon: self.horizon.lerp(other.horizon, t),
+            zenith: self.zenith.lerp(other.zenith, t),
+            ambient_intensity: self.ambient_intensity + (other.ambient_intensity - self.ambient_intensity) * t,
+        }
+    }
+}
+
+/// Moves the sun (and a moon opposite to it) through the day and drives the scene lighting
+#[derive(Copy, Clone, Debug)]
+pub struct DayCycle {
+    /// Local solar time in hours, 12.0 is noon
+    pub time_of_day: f32,
+    /// 0 is the first of January
+    pub day_of_year: u32,
+    /// Degrees, positive in the northern hemisphere
+    pub latitude: f32,
+    /// Simulated seconds per real second
+    pub speed: f32,
+}
+
+impl Default for DayCycle {
+    fn default() -> Self {
+        Self {
+            time_of_day: 12.0,
+            day_of_year: DAY_CYCLE_DAY_OF_YEAR,
+            latitude: DAY_CYCLE_LATITUDE,
+            speed: DAY_CYCLE_SPEED,
+        }
+    }
+}
+
+impl DayCycle {
+    pub fn update(&mut self, delta_time: f32) {
+        self.time_of_day += delta_time * self.speed / 3600.0;
+        while self.time_of_day >= HOURS_PER_DAY {
+            self.time_of_day -= HOURS_PER_DAY;
+            self.day_of_year = (self.day_of_year + 1) % DAYS_PER_YEAR;
+        }
+        while self.time_of_day < 0.0 {
+            self.time_of_day += HOURS_PER_DAY;
+            self.day_of_year = (self.day_of_year + DAYS_PER_YEAR - 1) % DAYS_PER_YEAR;
+        }
+    }
+
+    /// Direction towards the sun, +X is east, +Y up and +Z south
+    pub fn sun_direction(&self) -> Vec3 {
+        let declination = (AXIAL_TILT.to_radians())
+            * (TAU * (284 + self.day_of_year) as f32 / DAYS_PER_YEAR as f32).sin();
+        let hour_angle = (self.time_of_day - 12.0) / HOURS_PER_DAY * TAU;
+        let latitude = self.latitude.to_radians();
+
+        let east = -declination.cos() * hour_angle.sin();
+        let north = declination.sin() * latitude.cos()
+            - declination.cos() * hour_angle.cos() * latitude.sin();
+        let up = declination.sin() * latitude.sin()
+            + declination.cos() * hour_angle.cos() * latitude.cos();
+        Vec3::new(east, up, -north).normalize()
+    }
+
+    /// Writes the sun or moon light and the matching sky colours into `lighting`
+    pub fn apply(&self, lighting: &mut Lighting) {
+        let sun = self.sun_direction();
+        let elevation = sun.y;
+        lighting.sky_sun_direction = sun;
+
+        if elevation > 0.0 {
+            lighting.sun_direction = sun;
+            lighting.sun_color = HORIZON_SUN_COLOR.lerp(NOON_SUN_COLOR, smoothstep(0.0, 0.4, elevation));
+            lighting.sun_intensity = smoothstep(0.0, 0.1, elevation);
+        } else {
+            lighting.sun_direction = -sun;
+            lighting.sun_color = MOON_COLOR;
+            lighting.sun_intensity = MOON_INTENSITY * smoothstep(0.0, 0.1, -elevation);
+        }
+
+        let palette = if elevation > 0.0 {
+            TWILIGHT.lerp(Palette::day(), smoothstep(0.0, 0.3, elevation))
+        } else {
+            TWILIGHT.lerp(NIGHT, smoothstep(0.0, 0.2, -elevation))
+        };
+        lighting.sky_color = palette.sky;
+        lighting.ground_color = palette.ground;
+        lighting.horizon_color = palette.horizon;
+        lighting.zenith_color = palette.zenith;
+        lighting.ambient_intensity = palette.ambient_intensity;
+    }
+}
+
+fn smoothstep(edge0: f32, edge1: f32, x: f32) -> f32 {
+    let t = ((x - edge0) / (edge1 - edge0)).clamp(0.0, 1.0);
+    t * t * (3.0 - 2.0 * t)
+}
@@ -0,0 +1,29 @@
+use glam::Vec3;
+use crate::config::{FOG_DENSITY, FOG_HEIGHT_FALLOFF};
+
+/// Exponential height fog, thickest at ground level and thinning out with altitude
+#[derive(Copy, Clone, Debug)]
+pub struct Fog {
+    /// Extinction per metre at height zero, 0 disables the fog
+    pub density: f32,
+    /// How quickly the fog thins out with height, per metre
+    pub height_falloff: f32,
+    /// Used when `color_from_sky` is off
+    pub color: Vec3,
+    /// Take the colour from the sky at the horizon instead of `color`
+    pub color_from_sky: bool,
+    /// How much the fog brightens when looking towards the sun
+    pub sun_scattering: f32,
+}
+
+impl Default for Fog {
+    fn default() -> Self {
+        Self {
+            density: FOG_DENSITY,
+            height_falloff: FOG_HEIGHT_FALLOFF,
+            color: Vec3::new(0.6, 0.7, 0.8),
+            color_from_sky: true,
+            sun_scattering: 0.5,
+        }
+    }
+}
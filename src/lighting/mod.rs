@@ -0,0 +1,94 @@
+pub mod day_cycle;
+pub mod fog;
+pub mod translucency;
+
+use glam::Vec3;
+use crate::config::{SKY_COLOR, SUN_DIRECTION};
+use fog::Fog;
+use translucency::Translucency;
+
+/// Scene lighting shared by the grass and ground shaders
+#[derive(Copy, Clone, Debug)]
+pub struct Lighting {
+    /// Direction towards the sun
+    pub sun_direction: Vec3,
+    pub sun_color: Vec3,
+    pub sun_intensity: f32,
+    /// Hemisphere ambient colour from above
+    pub sky_color: Vec3,
+    /// Hemisphere ambient colour from below
+    pub ground_color: Vec3,
+    pub ambient_intensity: f32,
+    /// Sky colour at the horizon, also used where the sky is not drawn
+    pub horizon_color: Vec3,
+    /// Sky colour straight up
+    pub zenith_color: Vec3,
+    /// Where the sky draws the sun, differs from `sun_direction` while the moon lights the scene
+    pub sky_sun_direction: Vec3,
+    pub fog: Fog,
+    pub translucency: Translucency,
+}
+
+impl Default for Lighting {
+    fn default() -> Self {
+        Self {
+            sun_direction: Vec3::from(SUN_DIRECTION).normalize(),
+            sun_color: Vec3::new(1.0, 1.0, 0.9),
+            sun_intensity: 1.0,
+            sky_color: Vec3::new(1.0, 1.0, 0.5),
+            ground_color: Vec3::new(0.05, 0.05, 0.25),
+            ambient_intensity: 1.0,
+            horizon_color: Vec3::new(SKY_COLOR.r as f32, SKY_COLOR.g as f32, SKY_COLOR.b as f32),
+            zenith_color: Vec3::new(0.25, 0.45, 0.8),
+            sky_sun_direction: Vec3::from(SUN_DIRECTION).normalize(),
+            fog: Fog::default(),
+            translucency: Translucency::default(),
+        }
+    }
+}
+
+impl Lighting {
+    pub fn to_uniforms(self) -> LightingUniforms {
+        let fog_color = if self.fog.color_from_sky { self.horizon_color } else { self.fog.color };
+        LightingUniforms {
+            sun_direction: self.sun_direction.normalize_or(Vec3::Y).to_array(),
+            sun_intensity: self.sun_intensity,
+            sun_color: self.sun_color.to_array(),
+            ambient_intensity: self.ambient_intensity,
+            sky_color: self.sky_color.to_array(),
+            _padding1: 0.0,
+            ground_color: self.ground_color.to_array(),
+            _padding2: 0.0,
+            fog_color: fog_color.to_array(),
+            fog_density: self.fog.density,
+            fog_height_falloff: self.fog.height_falloff,
+            fog_sun_scattering: self.fog.sun_scattering,
+            translucency_distortion: self.translucency.distortion,
+            translucency_power: self.translucency.power,
+            translucency_strength: self.translucency.strength,
+            _padding3: [0.0; 3],
+        }
+    }
+}
+
+/// Matches `Lighting` in lighting.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct LightingUniforms {
+    pub sun_direction: [f32; 3],
+    pub sun_intensity: f32,
+    pub sun_color: [f32; 3],
+    pub ambient_intensity: f32,
+    pub sky_color: [f32; 3],
+    pub _padding1: f32,
+    pub ground_color: [f32; 3],
+    pub _padding2: f32,
+    pub fog_color: [f32; 3],
+    pub fog_density: f32,
+    pub fog_height_falloff: f32,
+    pub fog_sun_scattering: f32,
+    pub translucency_distortion: f32,
+    pub translucency_power: f32,
+    pub translucency_strength: f32,
+    pub _padding3: [f32; 3],
+}
@@ -0,0 +1,20 @@
+/// Sunlight transmitted through the grass blades towards the viewer
+#[derive(Copy, Clone, Debug)]
+pub struct Translucency {
+    /// Scales the transmitted light, 0 disables it
+    pub strength: f32,
+    /// How far the exit direction bends towards the blade normal
+    pub distortion: f32,
+    /// Tightness of the glow around the sun, higher is narrower
+    pub power: f32,
+}
+
+impl Default for Translucency {
+    fn default() -> Self {
+        Self {
+            strength: 4.0,
+            distortion: 0.3,
+            power: 3.0,
+        }
+    }
+}
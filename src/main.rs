@@ -1,34 +1,94 @@
+mod bench;
 mod camera;
 mod grass;
 mod renderer;
 mod config;
+mod wind;
+mod cli;
+mod clock;
+mod lighting;
+mod settings;
+mod ui;
+
+use std::path::PathBuf;
 
 use winit::{
     event::*,
     event_loop::{EventLoop, ControlFlow},
     application::ApplicationHandler,
-    dpi::LogicalSize,
+    dpi::{LogicalSize, PhysicalSize},
+    keyboard::{KeyCode, PhysicalKey},
 };
+use bench::{BenchSettings, Benchmark};
+use camera::path::CameraPath;
+use lighting::day_cycle::DayCycle;
+use lighting::fog::Fog;
+use renderer::Renderer;
+use renderer::debug_view::DebugView;
+use renderer::sky::SkyModel;
+use settings::Settings;
+use wind::WindField;
+use wind::emitter::{EmitterId, WindEmitter};
+
+/// Select the entries of `DebugView::ALL`
+const DEBUG_VIEW_KEYS: [KeyCode; 9] = [
+    KeyCode::F1,
+    KeyCode::F2,
+    KeyCode::F3,
+    KeyCode::F4,
+    KeyCode::F5,
+    KeyCode::F6,
+    KeyCode::F7,
+    KeyCode::F10,
+    KeyCode::F11,
+];
 
 struct App {
+    args: cli::Args,
     renderer: Option<renderer::Renderer>,
     window: Option<&'static winit::window::Window>,
+    ui: Option<ui::Ui>,
+    downwash: Option<EmitterId>,
+    /// Windowed benchmark run, the app exits once it is finished
+    bench: Option<Benchmark>,
 }
 
 impl ApplicationHandler for App {
     fn resumed(&mut self, event_loop: &winit::event_loop::ActiveEventLoop) {
         if self.window.is_none() {
-            let window_attributes = winit::window::Window::default_attributes()
+            let mut window_attributes = winit::window::Window::default_attributes()
                 .with_title("Procedural Grass")
                 .with_inner_size(LogicalSize::new(1280, 720));
+            // benchmarks render at an exact pixel size
+            if let Some(bench) = &self.bench {
+                let settings = bench.settings();
+                window_attributes = window_attributes
+                    .with_inner_size(PhysicalSize::new(settings.width, settings.height))
+                    .with_resizable(false);
+            }
             
             let window = event_loop.create_window(window_attributes).unwrap();
             let window = Box::leak(Box::new(window));
             
-            let renderer = pollster::block_on(renderer::Renderer::new(window));
+            let seed = self.bench.as_ref().map_or(self.args.seed, |bench| Some(bench.settings().seed));
+            let mut renderer = pollster::block_on(Renderer::new(window, self.args.msaa_samples, seed, self.args.shadow));
+            let settings_file = configure_renderer(&mut renderer, &self.args);
+            let Ok(camera_path) = load_camera_path(&self.args) else {
+                event_loop.exit();
+                return;
+            };
+            match (&mut self.bench, camera_path) {
+                (Some(bench), camera_path) => bench.start(&mut renderer, camera_path, settings_file),
+                (None, Some(camera_path)) => renderer.play_camera_path(camera_path),
+                (None, None) => {}
+            }
             
+            let mut ui = ui::Ui::new(window, self.args.settings_path.clone());
+            ui.visible = self.args.ui;
+
             self.window = Some(window);
             self.renderer = Some(renderer);
+            self.ui = Some(ui);
         }
     }
 
@@ -40,6 +100,10 @@ impl ApplicationHandler for App {
     ) {
         let Some(renderer) = self.renderer.as_mut() else { return };
         let Some(window) = self.window else { return };
+        let Some(ui) = self.ui.as_mut() else { return };
+        if ui.on_window_event(window, &event) {
+            return;
+        }
 
         match event {
             WindowEvent::CloseRequested => event_loop.exit(),
@@ -53,8 +117,196 @@ impl ApplicationHandler for App {
             WindowEvent::MouseWheel { delta, .. } => {
                 renderer.camera_controller_mut().process_scroll(delta);
             }
+            // held movement keys of the fly camera, anything else falls through
+            WindowEvent::KeyboardInput {
+                event: KeyEvent { physical_key: PhysicalKey::Code(key), state, .. },
+                ..
+            } if renderer.camera_controller_mut().process_keyboard(key, state) => {}
+            WindowEvent::KeyboardInput { event, .. }
+                if event.state == ElementState::Pressed && !event.repeat =>
+            {
+                // number keys switch between wind presets, B/V/G/H spawn local wind,
+                // [ and ] rotate the sun, N toggles the day cycle and T speeds it up,
+                // K switches the sky model, F toggles the fog, M switches the tonemapper,
+                // X toggles auto-exposure and page up/down change the exposure, Y toggles TAA,
+                // O advances the season, C switches between orbit and fly camera, L toggles
+                // ground following, R starts and stops recording a camera path, Z toggles the
+                // top-down view, Tab toggles the minimap, F1 to F7, F10 and F11 select the debug
+                // view, F8 toggles the wireframe, F9 the debug lines, U the parameter panels,
+                // P pauses, period steps and +/- change the time scale
+                let target = renderer.camera_controller_mut().focus_point();
+                let wind_angle = renderer.wind_mut().direction;
+                match event.physical_key {
+                    PhysicalKey::Code(KeyCode::Digit1) => *renderer.wind_mut() = WindField::calm(),
+                    PhysicalKey::Code(KeyCode::Digit2) => *renderer.wind_mut() = WindField::breeze(),
+                    PhysicalKey::Code(KeyCode::Digit3) => *renderer.wind_mut() = WindField::storm(),
+                    PhysicalKey::Code(KeyCode::KeyB) => {
+                        renderer.wind_emitters_mut().spawn(WindEmitter::radial(target, 12.0, 1.4, 1.5));
+                    }
+                    PhysicalKey::Code(KeyCode::KeyV) => {
+                        renderer.wind_emitters_mut().spawn(WindEmitter::vortex(target, 8.0, 0.8, 6.0));
+                    }
+                    PhysicalKey::Code(KeyCode::KeyG) => {
+                        renderer.wind_emitters_mut().spawn(WindEmitter::directional(
+                            target, wind_angle, 0.4, 20.0, 0.9, 4.0,
+                        ));
+                    }
+                    PhysicalKey::Code(KeyCode::KeyH) => match self.downwash.take() {
+                        Some(id) => renderer.wind_emitters_mut().remove(id),
+                        None => {
+                            let downwash = WindEmitter::radial(target, 6.0, 1.0, f32::INFINITY);
+                            self.downwash = Some(renderer.wind_emitters_mut().spawn(downwash));
+                        }
+                    },
+                    PhysicalKey::Code(KeyCode::BracketLeft) => {
+                        let lighting = renderer.lighting_mut();
+                        lighting.sun_direction = glam::Quat::from_rotation_y(-0.1) * lighting.sun_direction;
+                        lighting.sky_sun_direction = lighting.sun_direction;
+                    }
+                    PhysicalKey::Code(KeyCode::BracketRight) => {
+                        let lighting = renderer.lighting_mut();
+                        lighting.sun_direction = glam::Quat::from_rotation_y(0.1) * lighting.sun_direction;
+                        lighting.sky_sun_direction = lighting.sun_direction;
+                    }
+                    PhysicalKey::Code(KeyCode::KeyN) => {
+                        let day_cycle = match renderer.day_cycle_mut() {
+                            Some(_) => None,
+                            None => Some(DayCycle::default()),
+                        };
+                        log::info!("Day cycle {}", if day_cycle.is_some() { "enabled" } else { "disabled" });
+                        renderer.set_day_cycle(day_cycle);
+                    }
+                    PhysicalKey::Code(KeyCode::KeyT) => {
+                        if let Some(day_cycle) = renderer.day_cycle_mut() {
+                            day_cycle.speed = if day_cycle.speed >= 6000.0 { 60.0 } else { day_cycle.speed * 10.0 };
+                            log::info!("Day cycle speed {}x", day_cycle.speed);
+                        }
+                    }
+                    PhysicalKey::Code(KeyCode::KeyK) => {
+                        let sky = renderer.sky_settings_mut();
+                        sky.model = match sky.model {
+                            SkyModel::Atmosphere => SkyModel::Gradient,
+                            SkyModel::Gradient => SkyModel::Atmosphere,
+                        };
+                        log::info!("Sky model {:?}", sky.model);
+                    }
+                    PhysicalKey::Code(KeyCode::KeyF) => {
+                        let fog = &mut renderer.lighting_mut().fog;
+                        fog.density = if fog.density > 0.0 { 0.0 } else { Fog::default().density };
+                    }
+                    PhysicalKey::Code(KeyCode::KeyM) => {
+                        let tonemap = renderer.tonemap_settings_mut();
+                        tonemap.tonemapper = tonemap.tonemapper.next();
+                        log::info!("Tonemapper {:?}", tonemap.tonemapper);
+                    }
+                    PhysicalKey::Code(KeyCode::KeyX) => {
+                        let tonemap = renderer.tonemap_settings_mut();
+                        tonemap.auto_exposure = !tonemap.auto_exposure;
+                        log::info!("Auto exposure {}", if tonemap.auto_exposure { "on" } else { "off" });
+                    }
+                    PhysicalKey::Code(KeyCode::PageUp) | PhysicalKey::Code(KeyCode::PageDown) => {
+                        let tonemap = renderer.tonemap_settings_mut();
+                        let factor = if event.physical_key == PhysicalKey::Code(KeyCode::PageUp) { 1.25 } else { 0.8 };
+                        tonemap.exposure = (tonemap.exposure * factor).clamp(0.05, 20.0);
+                        log::info!("Exposure {}", tonemap.exposure);
+                    }
+                    PhysicalKey::Code(KeyCode::KeyY) => {
+                        let taa = renderer.taa_settings_mut();
+                        taa.enabled = !taa.enabled;
+                        log::info!("TAA {}", if taa.enabled { "on" } else { "off" });
+                    }
+                    PhysicalKey::Code(KeyCode::KeyO) => {
+                        let material = renderer.grass_material_mut();
+                        material.season = if material.season >= 1.0 { 0.0 } else { (material.season + 0.25).min(1.0) };
+                        log::info!("Season {}", material.season);
+                    }
+                    PhysicalKey::Code(KeyCode::KeyC) => {
+                        let controller = renderer.camera_controller_mut();
+                        controller.set_mode(controller.mode.next());
+                        log::info!("Camera mode {:?}", controller.mode);
+                    }
+                    PhysicalKey::Code(KeyCode::KeyL) => {
+                        let controller = renderer.camera_controller_mut();
+                        controller.ground_following = !controller.ground_following;
+                        log::info!("Ground following {}", if controller.ground_following { "on" } else { "off" });
+                    }
+                    PhysicalKey::Code(KeyCode::KeyR) => match renderer.stop_recording_camera_path() {
+                        Some(camera_path) => match camera_path.save(&self.args.record_path) {
+                            Ok(()) => log::info!("Saved camera path to {}", self.args.record_path.display()),
+                            Err(err) => log::error!(
+                                "Failed to save camera path {}: {}",
+                                self.args.record_path.display(),
+                                err
+                            ),
+                        },
+                        None => {
+                            renderer.start_recording_camera_path();
+                            log::info!("Recording camera path");
+                        }
+                    },
+                    PhysicalKey::Code(KeyCode::KeyZ) => {
+                        let top_down = !renderer.is_top_down();
+                        renderer.set_top_down(top_down);
+                        log::info!("Top-down view {}", if top_down { "on" } else { "off" });
+                    }
+                    PhysicalKey::Code(KeyCode::Tab) => {
+                        let minimap = renderer.minimap_settings_mut();
+                        minimap.enabled = !minimap.enabled;
+                        log::info!("Minimap {}", if minimap.enabled { "on" } else { "off" });
+                    }
+                    PhysicalKey::Code(KeyCode::F8) => {
+                        let wireframe = !renderer.is_wireframe();
+                        renderer.set_wireframe(wireframe);
+                        log::info!("Wireframe {}", if wireframe { "on" } else { "off" });
+                    }
+                    PhysicalKey::Code(KeyCode::F9) => {
+                        let debug_lines = renderer.debug_line_settings_mut();
+                        debug_lines.enabled = !debug_lines.enabled;
+                        log::info!("Debug lines {}", if debug_lines.enabled { "on" } else { "off" });
+                    }
+                    PhysicalKey::Code(KeyCode::KeyU) => {
+                        ui.visible = !ui.visible;
+                        log::info!("Parameter panels {}", if ui.visible { "shown" } else { "hidden" });
+                    }
+                    PhysicalKey::Code(KeyCode::KeyP) => {
+                        let clock = renderer.clock_mut();
+                        clock.toggle_pause();
+                        log::info!("Simulation {}", if clock.is_paused() { "paused" } else { "resumed" });
+                    }
+                    PhysicalKey::Code(KeyCode::Period) => renderer.clock_mut().step(),
+                    PhysicalKey::Code(KeyCode::Equal) | PhysicalKey::Code(KeyCode::Minus) => {
+                        let clock = renderer.clock_mut();
+                        let factor = if event.physical_key == PhysicalKey::Code(KeyCode::Equal) { 2.0 } else { 0.5 };
+                        clock.set_time_scale((clock.time_scale() * factor).clamp(0.125, 8.0));
+                        log::info!("Time scale {}", clock.time_scale());
+                    }
+                    PhysicalKey::Code(key) if DEBUG_VIEW_KEYS.contains(&key) => {
+                        let index = DEBUG_VIEW_KEYS.iter().position(|&k| k == key).unwrap_or(0);
+                        *renderer.debug_view_mut() = DebugView::ALL[index];
+                        log::info!("Debug view {:?}", DebugView::ALL[index]);
+                    }
+                    _ => {}
+                }
+            }
             WindowEvent::RedrawRequested => {
-                renderer.render();
+                ui.prepare(window, renderer);
+                match &mut self.bench {
+                    Some(bench) => {
+                        bench.frame(renderer);
+                        if bench.is_finished() {
+                            write_bench_report(bench, renderer);
+                            self.bench = None;
+                            event_loop.exit();
+                            return;
+                        }
+                    }
+                    None => {
+                        renderer.render();
+                        if self.args.play_path.is_some() && !renderer.is_playing_camera_path() {
+                            event_loop.exit();
+                        }
+                    }
+                }
                 window.request_redraw();
             }
             _ => {}
@@ -68,15 +320,102 @@ impl ApplicationHandler for App {
     }
 }
 
+/// Applies the command line options and the settings file to a new renderer,
+/// returns the settings file if one was applied
+///
+/// Benchmarks skip the default settings file so a file left over from tuning
+/// does not silently change their results.
+fn configure_renderer(renderer: &mut Renderer, args: &cli::Args) -> Option<PathBuf> {
+    if let Some(delta) = args.fixed_delta {
+        *renderer.clock_mut() = clock::SimulationClock::with_fixed_delta(delta);
+    }
+    renderer.set_day_cycle(args.day_cycle);
+    *renderer.tonemap_settings_mut() = args.tonemap;
+    *renderer.taa_settings_mut() = args.taa;
+    renderer.grass_material_mut().season = args.season;
+    renderer.set_top_down(args.top_down);
+    *renderer.debug_view_mut() = args.debug_view;
+    renderer.minimap_settings_mut().enabled = args.minimap;
+    renderer.set_wireframe(args.wireframe);
+    renderer.debug_line_settings_mut().enabled = args.debug_lines;
+    if let Some(path) = &args.flow_map {
+        if let Err(err) = renderer.load_flow_map(path, args.flow_map_settings) {
+            log::error!("Failed to load flow map {}: {}", path.display(), err);
+        }
+    }
+    if !args.settings_path.exists() || (args.bench.is_some() && !args.settings_given) {
+        return None;
+    }
+    match Settings::load(&args.settings_path) {
+        Ok(settings) => {
+            settings.apply(renderer);
+            log::info!("Loaded settings from {}", args.settings_path.display());
+            Some(args.settings_path.clone())
+        }
+        Err(err) => {
+            log::error!("Failed to load settings {}: {}", args.settings_path.display(), err);
+            None
+        }
+    }
+}
+
+/// The `--play-path` camera path, Err once the failure has been logged
+fn load_camera_path(args: &cli::Args) -> Result<Option<CameraPath>, ()> {
+    let Some(path) = &args.play_path else { return Ok(None) };
+    CameraPath::load(path).map(Some).map_err(|err| {
+        log::error!("Failed to load camera path {}: {}", path.display(), err);
+    })
+}
+
+fn write_bench_report(bench: &Benchmark, renderer: &Renderer) {
+    let report = bench.report(renderer);
+    report.print();
+    let output = &bench.settings().output;
+    match report.save(output) {
+        Ok(()) => println!("Wrote benchmark results to {}", output.display()),
+        Err(err) => log::error!("Failed to write benchmark results {}: {}", output.display(), err),
+    }
+}
+
+/// Runs the benchmark offscreen, without a window or event loop
+fn run_headless_bench(args: &cli::Args, settings: BenchSettings) {
+    let size = PhysicalSize::new(settings.width, settings.height);
+    let mut renderer = pollster::block_on(Renderer::new_headless(
+        size,
+        args.msaa_samples,
+        Some(settings.seed),
+        args.shadow,
+    ));
+    let settings_file = configure_renderer(&mut renderer, args);
+    let Ok(camera_path) = load_camera_path(args) else { return };
+
+    let mut bench = Benchmark::new(settings);
+    bench.start(&mut renderer, camera_path, settings_file);
+    while !bench.is_finished() {
+        bench.frame(&mut renderer);
+    }
+    write_bench_report(&bench, &renderer);
+}
+
 fn main() {
     env_logger::init();
+
+    let args = cli::Args::parse();
+    if let Some(settings) = args.bench.clone().filter(|settings| settings.headless) {
+        run_headless_bench(&args, settings);
+        return;
+    }
     
     let event_loop = EventLoop::new().unwrap();
     event_loop.set_control_flow(ControlFlow::Poll);
     
     let mut app = App {
+        bench: args.bench.clone().map(Benchmark::new),
+        args,
         renderer: None,
         window: None,
+        ui: None,
+        downwash: None,
     };
     
     event_loop.run_app(&mut app).unwrap();
@@ -1,10 +1,23 @@
 use wgpu::util::DeviceExt;
-use crate::grass::instance::GrassInstance;
+use crate::grass::instance::{BladeState, GrassInstance};
+use super::flow_map::FlowMap;
+
+/// Resources shared by both ping-pong bind groups
+struct SimulationInputs<'a> {
+    position_buffer: &'a wgpu::Buffer,
+    instance_buffer: &'a wgpu::Buffer,
+    wind_uniform_buffer: &'a wgpu::Buffer,
+    emitter_buffer: &'a wgpu::Buffer,
+}
 
 pub struct ComputeResources {
     pub pipeline: wgpu::ComputePipeline,
-    pub bind_group: wgpu::BindGroup,
+    // bind_groups[i] reads state_buffers[i] and writes the other one
+    pub bind_groups: [wgpu::BindGroup; 2],
+    pub flow_map_layout: wgpu::BindGroupLayout,
     pub _position_buffer: wgpu::Buffer,
+    pub _state_buffers: [wgpu::Buffer; 2],
+    current_state: usize,
 }
 
 impl ComputeResources {
@@ -13,6 +26,7 @@ impl ComputeResources {
         grass_positions: &[GrassInstance],
         instance_buffer: &wgpu::Buffer,
         wind_uniform_buffer: &wgpu::Buffer,
+        emitter_buffer: &wgpu::Buffer,
     ) -> Self {
         let position_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Grass Position Storage Buffer"),
@@ -20,29 +34,58 @@ impl ComputeResources {
             usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
         });
 
+        // blades start upright and at rest, buffers are zero initialized
+        let state_size = (grass_positions.len() * std::mem::size_of::<BladeState>()) as wgpu::BufferAddress;
+        let state_buffers = [
+            Self::create_state_buffer(device, "Blade State Buffer A", state_size),
+            Self::create_state_buffer(device, "Blade State Buffer B", state_size),
+        ];
+
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Compute Shader"),
             source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/compute.wgsl").into()),
         });
 
         let bind_group_layout = Self::create_bind_group_layout(device);
-        let bind_group = Self::create_bind_group(
-            device,
-            &bind_group_layout,
-            &position_buffer,
+        let inputs = SimulationInputs {
+            position_buffer: &position_buffer,
             instance_buffer,
             wind_uniform_buffer,
-        );
+            emitter_buffer,
+        };
+        let bind_groups = [0, 1].map(|step| {
+            Self::create_bind_group(device, &bind_group_layout, &inputs, &state_buffers, step)
+        });
 
-        let pipeline = Self::create_pipeline(device, &bind_group_layout, &shader);
+        let flow_map_layout = FlowMap::create_bind_group_layout(device);
+        let pipeline = Self::create_pipeline(device, &bind_group_layout, &flow_map_layout, &shader);
 
         Self {
             pipeline,
-            bind_group,
+            bind_groups,
+            flow_map_layout,
             _position_buffer: position_buffer,
+            _state_buffers: state_buffers,
+            current_state: 0,
         }
     }
 
+    fn create_state_buffer(device: &wgpu::Device, label: &str, size: wgpu::BufferAddress) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some(label),
+            size,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        })
+    }
+
+    /// Returns the bind group for the next simulation step and swaps the ping-pong buffers
+    pub fn next_step_bind_group(&mut self) -> &wgpu::BindGroup {
+        let bind_group = &self.bind_groups[self.current_state];
+        self.current_state = 1 - self.current_state;
+        bind_group
+    }
+
     fn create_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
         device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
             label: Some("Compute Bind Group Layout"),
@@ -77,6 +120,36 @@ impl ComputeResources {
                     },
                     count: None,
                 },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: false },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 5,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Storage { read_only: true },
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
             ],
         })
     }
@@ -84,9 +157,9 @@ impl ComputeResources {
     fn create_bind_group(
         device: &wgpu::Device,
         layout: &wgpu::BindGroupLayout,
-        position_buffer: &wgpu::Buffer,
-        instance_buffer: &wgpu::Buffer,
-        wind_uniform_buffer: &wgpu::Buffer,
+        inputs: &SimulationInputs,
+        state_buffers: &[wgpu::Buffer; 2],
+        step: usize,
     ) -> wgpu::BindGroup {
         device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("Compute Bind Group"),
@@ -94,15 +167,27 @@ impl ComputeResources {
             entries: &[
                 wgpu::BindGroupEntry {
                     binding: 0,
-                    resource: position_buffer.as_entire_binding(),
+                    resource: inputs.position_buffer.as_entire_binding(),
                 },
                 wgpu::BindGroupEntry {
                     binding: 1,
-                    resource: instance_buffer.as_entire_binding(),
+                    resource: inputs.instance_buffer.as_entire_binding(),
                 },
                 wgpu::BindGroupEntry {
                     binding: 2,
-                    resource: wind_uniform_buffer.as_entire_binding(),
+                    resource: inputs.wind_uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: state_buffers[step].as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 4,
+                    resource: state_buffers[1 - step].as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 5,
+                    resource: inputs.emitter_buffer.as_entire_binding(),
                 },
             ],
         })
@@ -111,11 +196,12 @@ impl ComputeResources {
     fn create_pipeline(
         device: &wgpu::Device,
         bind_group_layout: &wgpu::BindGroupLayout,
+        flow_map_layout: &wgpu::BindGroupLayout,
         shader: &wgpu::ShaderModule,
     ) -> wgpu::ComputePipeline {
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Compute Pipeline Layout"),
-            bind_group_layouts: &[bind_group_layout],
+            bind_group_layouts: &[bind_group_layout, flow_map_layout],
             push_constant_ranges: &[],
         });
 
@@ -128,4 +214,4 @@ impl ComputeResources {
             cache: None,
         })
     }
-}
\ No newline at end of file
+}
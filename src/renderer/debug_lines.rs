@@ -0,0 +1,118 @@
+use glam::Vec3;
+use crate::camera::Camera;
+use crate::config::FIELD_HALF_SIZE;
+use crate::wind::WindField;
+use super::hdr::{HdrTarget, HDR_FORMAT};
+use super::lines::{LineBatch, LineRenderer};
+
+/// How far the frozen camera frustum is drawn
+const FRUSTUM_DISTANCE: f32 = 30.0;
+/// Wind arrows per side of the field
+const WIND_ARROW_GRID: u32 = 5;
+/// Arrow length per unit of wind strength
+const WIND_ARROW_SCALE: f32 = 4.0;
+/// Height of the wind arrows above the ground
+const WIND_ARROW_HEIGHT: f32 = 1.5;
+/// Tallest blade relative to the average blade height, matches the height range in compute.wgsl
+const MAX_BLADE_HEIGHT_FACTOR: f32 = 1.2;
+
+const FIELD_BOUNDS_COLOR: Vec3 = Vec3::new(2.0, 2.0, 2.0);
+const GRASS_BOUNDS_COLOR: Vec3 = Vec3::new(0.3, 2.5, 0.3);
+const FRUSTUM_COLOR: Vec3 = Vec3::new(3.0, 2.4, 0.3);
+const WIND_ARROW_COLOR: Vec3 = Vec3::new(0.3, 1.2, 3.0);
+
+#[derive(Copy, Clone, Debug)]
+pub struct DebugLineSettings {
+    pub enabled: bool,
+    pub field_bounds: bool,
+    /// Box the blades can reach, the whole field is a single chunk
+    pub grass_bounds: bool,
+    /// Frustum of the camera at the moment the lines were switched on
+    pub frustum: bool,
+    /// Base wind direction and strength across the field
+    pub wind_arrows: bool,
+}
+
+impl Default for DebugLineSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            field_bounds: true,
+            grass_bounds: true,
+            frustum: true,
+            wind_arrows: true,
+        }
+    }
+}
+
+/// World space lines drawn over the main view after the scene is resolved
+pub struct DebugLines {
+    pub settings: DebugLineSettings,
+    lines: LineRenderer,
+    line_batch: LineBatch,
+    /// Near and far corners, kept while the lines stay on so the frustum can be inspected from elsewhere
+    frozen_frustum: Option<([Vec3; 4], [Vec3; 4])>,
+}
+
+impl DebugLines {
+    pub fn new(device: &wgpu::Device) -> Self {
+        Self {
+            settings: DebugLineSettings::default(),
+            lines: LineRenderer::new(device, HDR_FORMAT),
+            line_batch: LineBatch::default(),
+            frozen_frustum: None,
+        }
+    }
+
+    pub fn update(&mut self, queue: &wgpu::Queue, camera: &Camera, wind: &WindField, blade_height: f32) {
+        if !self.settings.enabled {
+            self.frozen_frustum = None;
+            return;
+        }
+        let frozen_frustum = *self.frozen_frustum.get_or_insert_with(|| {
+            (camera.frustum_corners(0.0), camera.frustum_corners(FRUSTUM_DISTANCE))
+        });
+
+        self.line_batch.clear();
+        if self.settings.field_bounds {
+            let corners = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+                .map(|(x, z)| Vec3::new(x * FIELD_HALF_SIZE, 0.0, z * FIELD_HALF_SIZE));
+            self.line_batch.polygon(&corners, FIELD_BOUNDS_COLOR);
+        }
+        if self.settings.grass_bounds {
+            self.line_batch.aabb(
+                Vec3::new(-FIELD_HALF_SIZE, 0.0, -FIELD_HALF_SIZE),
+                Vec3::new(FIELD_HALF_SIZE, blade_height * MAX_BLADE_HEIGHT_FACTOR, FIELD_HALF_SIZE),
+                GRASS_BOUNDS_COLOR,
+            );
+        }
+        if self.settings.frustum {
+            let (near, far) = frozen_frustum;
+            self.line_batch.frustum(&near, &far, FRUSTUM_COLOR);
+        }
+        if self.settings.wind_arrows {
+            let direction = Vec3::new(wind.direction.cos(), 0.0, wind.direction.sin());
+            let arrow = direction * wind.strength * WIND_ARROW_SCALE;
+            let spacing = 2.0 * FIELD_HALF_SIZE / WIND_ARROW_GRID as f32;
+            for i in 0..WIND_ARROW_GRID {
+                for j in 0..WIND_ARROW_GRID {
+                    let center = Vec3::new(
+                        -FIELD_HALF_SIZE + (i as f32 + 0.5) * spacing,
+                        WIND_ARROW_HEIGHT,
+                        -FIELD_HALF_SIZE + (j as f32 + 0.5) * spacing,
+                    );
+                    self.line_batch.arrow(center - arrow * 0.5, center + arrow * 0.5, WIND_ARROW_COLOR);
+                }
+            }
+        }
+
+        // drawn after the TAA resolve, so without the jitter
+        self.lines.update(queue, camera.build_unjittered_view_projection_matrix(), &self.line_batch);
+    }
+
+    pub fn run(&self, encoder: &mut wgpu::CommandEncoder, hdr: &HdrTarget) {
+        if self.settings.enabled {
+            self.lines.run(encoder, &hdr.view);
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use crate::config::{GRASS_CLUMP_SIZE, GRASS_LOD_DISTANCES};
+
+/// What the grass and ground shaders output instead of the lit colour
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum DebugView {
+    #[default]
+    None,
+    /// World space blade normals
+    Normals,
+    /// How far the wind bends each blade, blue is still and red fully bent
+    WindSway,
+    /// Random value of each blade as grey
+    BladeHash,
+    /// One colour per grass species
+    Species,
+    /// Position along the blade, black at the root and white at the tip
+    HeightFactor,
+    /// Blade fragments drawn per pixel, ignoring depth
+    Overdraw,
+    /// One colour per camera distance band of `GRASS_LOD_DISTANCES`
+    LodLevel,
+    /// One colour per blade clump of `GRASS_CLUMP_SIZE`
+    ClumpId,
+}
+
+impl DebugView {
+    /// In the order of the F1 to F7, F10 and F11 keys
+    pub const ALL: [DebugView; 9] = [
+        DebugView::None,
+        DebugView::Normals,
+        DebugView::WindSway,
+        DebugView::BladeHash,
+        DebugView::Species,
+        DebugView::HeightFactor,
+        DebugView::Overdraw,
+        DebugView::LodLevel,
+        DebugView::ClumpId,
+    ];
+
+    fn as_gpu(self) -> u32 {
+        match self {
+            DebugView::None => 0,
+            DebugView::Normals => 1,
+            DebugView::WindSway => 2,
+            DebugView::BladeHash => 3,
+            DebugView::Species => 4,
+            DebugView::HeightFactor => 5,
+            DebugView::Overdraw => 6,
+            DebugView::LodLevel => 7,
+            DebugView::ClumpId => 8,
+        }
+    }
+
+    /// `barycentric_wireframe` draws the wireframe in the shaders, for adapters without line polygons
+    pub fn to_uniforms(self, barycentric_wireframe: bool) -> DebugUniforms {
+        DebugUniforms {
+            view: self.as_gpu(),
+            barycentric_wireframe: barycentric_wireframe as u32,
+            lod_distances: GRASS_LOD_DISTANCES,
+            clump_size: GRASS_CLUMP_SIZE,
+            _padding: [0; 3],
+        }
+    }
+}
+
+/// Matches `DebugUniforms` in debug.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct DebugUniforms {
+    pub view: u32,
+    pub barycentric_wireframe: u32,
+    pub lod_distances: [f32; 2],
+    pub clump_size: f32,
+    pub _padding: [u32; 3],
+}
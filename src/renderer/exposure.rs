@@ -0,0 +1,227 @@
+use wgpu::util::DeviceExt;
+
+/// Matches `BIN_COUNT` in exposure.wgsl
+const HISTOGRAM_BINS: usize = 256;
+
+#[derive(Copy, Clone, Debug)]
+pub struct AutoExposureSettings {
+    /// Darkest luminance (log2) the histogram resolves
+    pub min_log_luminance: f32,
+    /// Brightest luminance (log2) the histogram resolves
+    pub max_log_luminance: f32,
+    /// How fast the eye adapts, higher is faster
+    pub adaptation_speed: f32,
+}
+
+impl Default for AutoExposureSettings {
+    fn default() -> Self {
+        Self {
+            min_log_luminance: -8.0,
+            max_log_luminance: 4.0,
+            adaptation_speed: 1.5,
+        }
+    }
+}
+
+/// Matches `ExposureUniforms` in exposure.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct ExposureUniforms {
+    min_log_luminance: f32,
+    log_luminance_range: f32,
+    delta_time: f32,
+    adaptation_speed: f32,
+    pixel_count: u32,
+    _padding: [u32; 3],
+}
+
+/// Measures the average scene luminance on the GPU from a histogram of the HDR target
+pub struct AutoExposure {
+    pub settings: AutoExposureSettings,
+    histogram_pipeline: wgpu::ComputePipeline,
+    average_pipeline: wgpu::ComputePipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    bind_group: wgpu::BindGroup,
+    histogram_buffer: wgpu::Buffer,
+    /// Adapted average luminance, read by the tonemap pass as a uniform
+    pub luminance_buffer: wgpu::Buffer,
+    uniform_buffer: wgpu::Buffer,
+}
+
+impl AutoExposure {
+    pub fn new(device: &wgpu::Device, hdr_view: &wgpu::TextureView) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Exposure Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/exposure.wgsl").into()),
+        });
+
+        let histogram_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Luminance Histogram Buffer"),
+            size: (HISTOGRAM_BINS * std::mem::size_of::<u32>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE,
+            mapped_at_creation: false,
+        });
+
+        let luminance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Average Luminance Buffer"),
+            contents: bytemuck::cast_slice(&[0.18_f32, 0.0, 0.0, 0.0]),
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::UNIFORM,
+        });
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Exposure Uniform Buffer"),
+            size: std::mem::size_of::<ExposureUniforms>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let storage_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::COMPUTE,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Storage { read_only: false },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Exposure Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                storage_entry(1),
+                storage_entry(2),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::COMPUTE,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Exposure Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let create_pipeline = |label, entry_point| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: Some(label),
+                layout: Some(&pipeline_layout),
+                module: &shader,
+                entry_point: Some(entry_point),
+                compilation_options: Default::default(),
+                cache: None,
+            })
+        };
+        let histogram_pipeline = create_pipeline("Luminance Histogram Pipeline", "build_histogram");
+        let average_pipeline = create_pipeline("Average Luminance Pipeline", "average_luminance");
+
+        let bind_group = Self::create_bind_group(
+            device,
+            &bind_group_layout,
+            hdr_view,
+            &histogram_buffer,
+            &luminance_buffer,
+            &uniform_buffer,
+        );
+
+        Self {
+            settings: AutoExposureSettings::default(),
+            histogram_pipeline,
+            average_pipeline,
+            bind_group_layout,
+            bind_group,
+            histogram_buffer,
+            luminance_buffer,
+            uniform_buffer,
+        }
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        hdr_view: &wgpu::TextureView,
+        histogram_buffer: &wgpu::Buffer,
+        luminance_buffer: &wgpu::Buffer,
+        uniform_buffer: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Exposure Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(hdr_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: histogram_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: luminance_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 3,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+            ],
+        })
+    }
+
+    /// Points the histogram at a new HDR target after a resize
+    pub fn set_hdr_view(&mut self, device: &wgpu::Device, hdr_view: &wgpu::TextureView) {
+        self.bind_group = Self::create_bind_group(
+            device,
+            &self.bind_group_layout,
+            hdr_view,
+            &self.histogram_buffer,
+            &self.luminance_buffer,
+            &self.uniform_buffer,
+        );
+    }
+
+    pub fn run(
+        &self,
+        encoder: &mut wgpu::CommandEncoder,
+        queue: &wgpu::Queue,
+        size: (u32, u32),
+        delta_time: f32,
+    ) {
+        let uniforms = ExposureUniforms {
+            min_log_luminance: self.settings.min_log_luminance,
+            log_luminance_range: self.settings.max_log_luminance - self.settings.min_log_luminance,
+            delta_time,
+            adaptation_speed: self.settings.adaptation_speed,
+            pixel_count: size.0 * size.1,
+            _padding: [0; 3],
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&uniforms));
+
+        let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
+            label: Some("Auto Exposure Pass"),
+            timestamp_writes: None,
+        });
+        compute_pass.set_bind_group(0, &self.bind_group, &[]);
+        compute_pass.set_pipeline(&self.histogram_pipeline);
+        compute_pass.dispatch_workgroups(size.0.div_ceil(16), size.1.div_ceil(16), 1);
+        compute_pass.set_pipeline(&self.average_pipeline);
+        compute_pass.dispatch_workgroups(1, 1, 1);
+    }
+}
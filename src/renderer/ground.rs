@@ -1,19 +1,30 @@
 use wgpu::util::DeviceExt;
+use crate::config::FIELD_HALF_SIZE;
+use super::depth::{DEPTH_COMPARE, DEPTH_FORMAT};
+use super::hdr::VELOCITY_FORMAT;
+use super::shadow::SHADOW_FORMAT;
 
 pub struct Ground {
     pub vertex_buffer: wgpu::Buffer,
     pub index_buffer: wgpu::Buffer,
     pub num_indices: u32,
+    /// Triangle corners in draw order, for the barycentric wireframe
+    pub wireframe_vertex_buffer: wgpu::Buffer,
     pub pipeline: wgpu::RenderPipeline,
+    /// Draws the triangle edges, `None` without `POLYGON_MODE_LINE`
+    pub wireframe_pipeline: Option<wgpu::RenderPipeline>,
+    pub shadow_pipeline: wgpu::RenderPipeline,
 }
 
 impl Ground {
     pub fn new(
         device: &wgpu::Device,
         format: wgpu::TextureFormat,
+        sample_count: u32,
         bind_group_layout: &wgpu::BindGroupLayout,
+        shadow_bind_group_layout: &wgpu::BindGroupLayout,
     ) -> Self {
-        let half_size = 25.0_f32; // from the grass positioning range
+        let half_size = FIELD_HALF_SIZE;
         
         //quad
         let vertices: Vec<[f32; 3]> = vec![
@@ -40,59 +51,117 @@ impl Ground {
             usage: wgpu::BufferUsages::INDEX,
         });
 
-        let pipeline = Self::create_pipeline(device, format, bind_group_layout);
+        let wireframe_vertices = indices
+            .iter()
+            .map(|&index| vertices[index as usize])
+            .collect::<Vec<_>>();
+        let wireframe_vertex_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Ground Wireframe Vertex Buffer"),
+            contents: bytemuck::cast_slice(&wireframe_vertices),
+            usage: wgpu::BufferUsages::VERTEX,
+        });
+
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Ground Shader"),
+            source: wgpu::ShaderSource::Wgsl(
+                concat!(
+                    include_str!("../shaders/shadow.wgsl"),
+                    include_str!("../shaders/lighting.wgsl"),
+                    include_str!("../shaders/temporal.wgsl"),
+                    include_str!("../shaders/debug.wgsl"),
+                    include_str!("../shaders/ground.wgsl"),
+                )
+                .into(),
+            ),
+        });
+
+        let pipeline = Self::create_pipeline(
+            device,
+            &shader,
+            format,
+            sample_count,
+            bind_group_layout,
+            shadow_bind_group_layout,
+            wgpu::PolygonMode::Fill,
+        );
+        let wireframe_pipeline = device
+            .features()
+            .contains(wgpu::Features::POLYGON_MODE_LINE)
+            .then(|| {
+                Self::create_pipeline(
+                    device,
+                    &shader,
+                    format,
+                    sample_count,
+                    bind_group_layout,
+                    shadow_bind_group_layout,
+                    wgpu::PolygonMode::Line,
+                )
+            });
+        let shadow_pipeline = Self::create_shadow_pipeline(device, &shader, bind_group_layout);
 
         Self {
             vertex_buffer,
             index_buffer,
             num_indices: indices.len() as u32,
+            wireframe_vertex_buffer,
             pipeline,
+            wireframe_pipeline,
+            shadow_pipeline,
+        }
+    }
+
+    fn vertex_buffer_layout() -> wgpu::VertexBufferLayout<'static> {
+        wgpu::VertexBufferLayout {
+            array_stride: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
+            step_mode: wgpu::VertexStepMode::Vertex,
+            attributes: &[wgpu::VertexAttribute {
+                offset: 0,
+                shader_location: 0,
+                format: wgpu::VertexFormat::Float32x3,
+            }],
         }
     }
 
     fn create_pipeline(
         device: &wgpu::Device,
+        shader: &wgpu::ShaderModule,
         format: wgpu::TextureFormat,
+        sample_count: u32,
         bind_group_layout: &wgpu::BindGroupLayout,
+        shadow_bind_group_layout: &wgpu::BindGroupLayout,
+        polygon_mode: wgpu::PolygonMode,
     ) -> wgpu::RenderPipeline {
-        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-            label: Some("Ground Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/ground.wgsl").into()),
-        });
-
         let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Ground Pipeline Layout"),
-            bind_group_layouts: &[bind_group_layout],
+            bind_group_layouts: &[bind_group_layout, shadow_bind_group_layout],
             push_constant_ranges: &[],
         });
 
-        let vertex_buffer_layout = wgpu::VertexBufferLayout {
-            array_stride: std::mem::size_of::<[f32; 3]>() as wgpu::BufferAddress,
-            step_mode: wgpu::VertexStepMode::Vertex,
-            attributes: &[wgpu::VertexAttribute {
-                offset: 0,
-                shader_location: 0,
-                format: wgpu::VertexFormat::Float32x3,
-            }],
-        };
-
         device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
             label: Some("Ground Render Pipeline"),
             layout: Some(&pipeline_layout),
             vertex: wgpu::VertexState {
-                module: &shader,
+                module: shader,
                 entry_point: Some("vs_main"),
-                buffers: &[vertex_buffer_layout],
+                buffers: &[Self::vertex_buffer_layout()],
                 compilation_options: Default::default(),
             },
             fragment: Some(wgpu::FragmentState {
-                module: &shader,
+                module: shader,
                 entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
+                targets: &[
+                    Some(wgpu::ColorTargetState {
+                        format,
+                        blend: Some(wgpu::BlendState::REPLACE),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    }),
+                    Some(wgpu::ColorTargetState {
+                        format: VELOCITY_FORMAT,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    }),
+                ],
                 compilation_options: Default::default(),
             }),
             primitive: wgpu::PrimitiveState {
@@ -100,19 +169,19 @@ impl Ground {
                 strip_index_format: None,
                 front_face: wgpu::FrontFace::Ccw,
                 cull_mode: Some(wgpu::Face::Back),
-                polygon_mode: wgpu::PolygonMode::Fill,
+                polygon_mode,
                 unclipped_depth: false,
                 conservative: false,
             },
             depth_stencil: Some(wgpu::DepthStencilState {
-                format: wgpu::TextureFormat::Depth32Float,
+                format: DEPTH_FORMAT,
                 depth_write_enabled: true,
-                depth_compare: wgpu::CompareFunction::Less,
+                depth_compare: DEPTH_COMPARE,
                 stencil: wgpu::StencilState::default(),
                 bias: wgpu::DepthBiasState::default(),
             }),
             multisample: wgpu::MultisampleState {
-                count: 1,
+                count: sample_count,
                 mask: !0,
                 alpha_to_coverage_enabled: false,
             },
@@ -120,4 +189,47 @@ impl Ground {
             cache: None,
         })
     }
-}
\ No newline at end of file
+
+    fn create_shadow_pipeline(
+        device: &wgpu::Device,
+        shader: &wgpu::ShaderModule,
+        bind_group_layout: &wgpu::BindGroupLayout,
+    ) -> wgpu::RenderPipeline {
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Ground Shadow Pipeline Layout"),
+            bind_group_layouts: &[bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Ground Shadow Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: shader,
+                entry_point: Some("vs_main"),
+                buffers: &[Self::vertex_buffer_layout()],
+                compilation_options: Default::default(),
+            },
+            fragment: None,
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::TriangleList,
+                cull_mode: None,
+                ..Default::default()
+            },
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: SHADOW_FORMAT,
+                depth_write_enabled: true,
+                depth_compare: wgpu::CompareFunction::Less,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState {
+                    constant: 2,
+                    slope_scale: 2.0,
+                    clamp: 0.0,
+                },
+            }),
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        })
+    }
+}
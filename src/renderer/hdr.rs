@@ -0,0 +1,88 @@
+/// Format of the intermediate target the scene is rendered into before tonemapping
+pub const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+/// Format of the screen space motion vectors written alongside the scene colour
+pub const VELOCITY_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rg16Float;
+
+/// Colour attachment of the scene pass, resolved to a single sampled texture with MSAA
+pub struct HdrTarget {
+    pub texture: wgpu::Texture,
+    /// Single sampled, read by the post processing passes
+    pub view: wgpu::TextureView,
+    pub _msaa_texture: Option<wgpu::Texture>,
+    /// Rendered into and resolved to `view` when MSAA is enabled
+    pub msaa_view: Option<wgpu::TextureView>,
+}
+
+impl HdrTarget {
+    pub fn new(device: &wgpu::Device, width: u32, height: u32, sample_count: u32) -> Self {
+        Self::with_format(device, "HDR", HDR_FORMAT, width, height, sample_count)
+    }
+
+    /// Motion vector target, resolved like the colour
+    pub fn velocity(device: &wgpu::Device, width: u32, height: u32, sample_count: u32) -> Self {
+        Self::with_format(device, "Velocity", VELOCITY_FORMAT, width, height, sample_count)
+    }
+
+    fn with_format(
+        device: &wgpu::Device,
+        name: &str,
+        format: wgpu::TextureFormat,
+        width: u32,
+        height: u32,
+        sample_count: u32,
+    ) -> Self {
+        let create_texture = |label: &str, sample_count, usage| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some(label),
+                size: wgpu::Extent3d {
+                    width,
+                    height,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count,
+                dimension: wgpu::TextureDimension::D2,
+                format,
+                usage,
+                view_formats: &[],
+            })
+        };
+
+        // TAA copies its resolved history back into the target, the minimap is copied out of its own
+        let texture = create_texture(
+            &format!("{} Texture", name),
+            1,
+            wgpu::TextureUsages::RENDER_ATTACHMENT
+                | wgpu::TextureUsages::TEXTURE_BINDING
+                | wgpu::TextureUsages::COPY_SRC
+                | wgpu::TextureUsages::COPY_DST,
+        );
+        let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let msaa_texture = (sample_count > 1).then(|| {
+            create_texture(
+                &format!("{} MSAA Texture", name),
+                sample_count,
+                wgpu::TextureUsages::RENDER_ATTACHMENT,
+            )
+        });
+        let msaa_view = msaa_texture
+            .as_ref()
+            .map(|texture| texture.create_view(&wgpu::TextureViewDescriptor::default()));
+
+        Self {
+            texture,
+            view,
+            _msaa_texture: msaa_texture,
+            msaa_view,
+        }
+    }
+
+    /// Colour attachment view and resolve target for the scene pass
+    pub fn attachment(&self) -> (&wgpu::TextureView, Option<&wgpu::TextureView>) {
+        match &self.msaa_view {
+            Some(msaa_view) => (msaa_view, Some(&self.view)),
+            None => (&self.view, None),
+        }
+    }
+}
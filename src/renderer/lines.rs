@@ -0,0 +1,218 @@
+use glam::{Mat4, Vec3};
+
+/// Line vertices uploaded per frame, anything past this is dropped
+const MAX_LINE_VERTICES: usize = 4096;
+
+/// Matches `VertexInput` in lines.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct LineVertex {
+    pub position: [f32; 3],
+    pub color: [f32; 3],
+}
+
+/// Line segments collected for one frame, every two vertices form a segment
+#[derive(Default)]
+pub struct LineBatch {
+    vertices: Vec<LineVertex>,
+}
+
+impl LineBatch {
+    pub fn clear(&mut self) {
+        self.vertices.clear();
+    }
+
+    pub fn line(&mut self, start: Vec3, end: Vec3, color: Vec3) {
+        self.vertices.push(LineVertex {
+            position: start.to_array(),
+            color: color.to_array(),
+        });
+        self.vertices.push(LineVertex {
+            position: end.to_array(),
+            color: color.to_array(),
+        });
+    }
+
+    /// Closed loop through `points`
+    pub fn polygon(&mut self, points: &[Vec3], color: Vec3) {
+        for (i, &point) in points.iter().enumerate() {
+            self.line(point, points[(i + 1) % points.len()], color);
+        }
+    }
+
+    pub fn aabb(&mut self, min: Vec3, max: Vec3, color: Vec3) {
+        let corner = |x: bool, y: bool, z: bool| {
+            Vec3::new(
+                if x { max.x } else { min.x },
+                if y { max.y } else { min.y },
+                if z { max.z } else { min.z },
+            )
+        };
+        for y in [false, true] {
+            self.polygon(
+                &[corner(false, y, false), corner(true, y, false), corner(true, y, true), corner(false, y, true)],
+                color,
+            );
+        }
+        for (x, z) in [(false, false), (true, false), (true, true), (false, true)] {
+            self.line(corner(x, false, z), corner(x, true, z), color);
+        }
+    }
+
+    /// Line with a head at `end`, the head lies flat in the XZ plane
+    pub fn arrow(&mut self, start: Vec3, end: Vec3, color: Vec3) {
+        self.line(start, end, color);
+        let back = (start - end) * 0.25;
+        let side = Vec3::new(-back.z, 0.0, back.x) * 0.5;
+        self.line(end, end + back + side, color);
+        self.line(end, end + back - side, color);
+    }
+
+    /// Outline of a frustum between two cross-sections, as given by `Camera::frustum_corners`
+    pub fn frustum(&mut self, near: &[Vec3; 4], far: &[Vec3; 4], color: Vec3) {
+        for (&near_corner, &far_corner) in near.iter().zip(far) {
+            self.line(near_corner, far_corner, color);
+        }
+        self.polygon(near, color);
+        self.polygon(far, color);
+    }
+}
+
+/// Draws a `LineBatch` on top of an HDR target
+pub struct LineRenderer {
+    pipeline: wgpu::RenderPipeline,
+    uniform_buffer: wgpu::Buffer,
+    bind_group: wgpu::BindGroup,
+    vertex_buffer: wgpu::Buffer,
+    vertex_count: u32,
+}
+
+impl LineRenderer {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Line Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/lines.wgsl").into()),
+        });
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Line Uniform Buffer"),
+            size: std::mem::size_of::<[f32; 16]>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let vertex_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Line Vertex Buffer"),
+            size: (MAX_LINE_VERTICES * std::mem::size_of::<LineVertex>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Line Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::VERTEX,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Line Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Line Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Line Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[wgpu::VertexBufferLayout {
+                    array_stride: std::mem::size_of::<LineVertex>() as wgpu::BufferAddress,
+                    step_mode: wgpu::VertexStepMode::Vertex,
+                    attributes: &wgpu::vertex_attr_array![0 => Float32x3, 1 => Float32x3],
+                }],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState {
+                topology: wgpu::PrimitiveTopology::LineList,
+                ..Default::default()
+            },
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            pipeline,
+            uniform_buffer,
+            bind_group,
+            vertex_buffer,
+            vertex_count: 0,
+        }
+    }
+
+    /// Uploads the camera and this frame's lines
+    pub fn update(&mut self, queue: &wgpu::Queue, view_proj: Mat4, batch: &LineBatch) {
+        let vertices = &batch.vertices[..batch.vertices.len().min(MAX_LINE_VERTICES)];
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::cast_slice(view_proj.as_ref()));
+        if !vertices.is_empty() {
+            queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(vertices));
+        }
+        self.vertex_count = vertices.len() as u32;
+    }
+
+    /// Draws the lines on top of whatever `view` holds
+    pub fn run(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        if self.vertex_count == 0 {
+            return;
+        }
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Line Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_pipeline(&self.pipeline);
+        render_pass.set_bind_group(0, &self.bind_group, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.draw(0..self.vertex_count, 0..1);
+    }
+}
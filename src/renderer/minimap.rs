@@ -0,0 +1,156 @@
+use glam::Vec3;
+use crate::camera::{Camera, Projection};
+use crate::config::FIELD_HALF_SIZE;
+use super::depth::DepthTexture;
+use super::hdr::{HdrTarget, HDR_FORMAT};
+use super::lines::{LineBatch, LineRenderer};
+
+/// Side length of the minimap in pixels
+const MINIMAP_SIZE: u32 = 256;
+/// Gap between the minimap and the top right corner of the window, in pixels
+const MINIMAP_MARGIN: u32 = 16;
+/// Height of the minimap camera above the field
+const MINIMAP_CAMERA_HEIGHT: f32 = 50.0;
+/// Share of the minimap taken up by the field
+const MINIMAP_FIELD_COVERAGE: f32 = 0.8;
+
+const FIELD_BOUNDS_COLOR: Vec3 = Vec3::new(2.0, 2.0, 2.0);
+const FRUSTUM_COLOR: Vec3 = Vec3::new(3.0, 2.4, 0.3);
+
+#[derive(Copy, Clone, Debug)]
+pub struct MinimapSettings {
+    pub enabled: bool,
+    /// How far the main camera frustum is drawn
+    pub frustum_distance: f32,
+}
+
+impl Default for MinimapSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            frustum_distance: 30.0,
+        }
+    }
+}
+
+/// Top-down orthographic view of the field, drawn into a corner of the HDR target
+pub struct Minimap {
+    pub settings: MinimapSettings,
+    camera: Camera,
+    camera_buffer: wgpu::Buffer,
+    camera_position_buffer: wgpu::Buffer,
+    /// Render bind group of the main view with this camera swapped in
+    pub render_bind_group: wgpu::BindGroup,
+    pub hdr: HdrTarget,
+    pub velocity: HdrTarget,
+    pub depth: DepthTexture,
+    lines: LineRenderer,
+    line_batch: LineBatch,
+}
+
+impl Minimap {
+    /// `shared_entries` are the render bind group entries of the main view besides the view projection
+    pub fn new(
+        device: &wgpu::Device,
+        sample_count: u32,
+        render_bind_group_layout: &wgpu::BindGroupLayout,
+        shared_entries: &[wgpu::BindGroupEntry],
+    ) -> Self {
+        let mut camera = Camera::new(Vec3::Y * MINIMAP_CAMERA_HEIGHT, Vec3::ZERO, 1.0);
+        camera.projection = Projection::Orthographic {
+            height: 2.0 * FIELD_HALF_SIZE / MINIMAP_FIELD_COVERAGE,
+        };
+
+        let camera_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Minimap Camera Buffer"),
+            size: std::mem::size_of::<[f32; 16]>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let camera_position_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Minimap Camera Position Buffer"),
+            size: std::mem::size_of::<[f32; 4]>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        // lighting, wind and material are shared, only the camera differs
+        let mut entries = vec![
+            wgpu::BindGroupEntry {
+                binding: 0,
+                resource: camera_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: camera_position_buffer.as_entire_binding(),
+            },
+        ];
+        entries.extend(shared_entries.iter().filter(|entry| entry.binding != 2).cloned());
+        let render_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Minimap Render Bind Group"),
+            layout: render_bind_group_layout,
+            entries: &entries,
+        });
+
+        Self {
+            settings: MinimapSettings::default(),
+            camera,
+            camera_buffer,
+            camera_position_buffer,
+            render_bind_group,
+            hdr: HdrTarget::new(device, MINIMAP_SIZE, MINIMAP_SIZE, sample_count),
+            velocity: HdrTarget::velocity(device, MINIMAP_SIZE, MINIMAP_SIZE, sample_count),
+            depth: DepthTexture::new(device, MINIMAP_SIZE, MINIMAP_SIZE, sample_count),
+            lines: LineRenderer::new(device, HDR_FORMAT),
+            line_batch: LineBatch::default(),
+        }
+    }
+
+    /// Uploads the minimap camera and the outline of `main_camera`'s frustum
+    pub fn update(&mut self, queue: &wgpu::Queue, main_camera: &Camera) {
+        let view_proj = self.camera.build_view_projection_matrix();
+        queue.write_buffer(&self.camera_buffer, 0, bytemuck::cast_slice(view_proj.as_ref()));
+        let position = self.camera.position;
+        queue.write_buffer(
+            &self.camera_position_buffer,
+            0,
+            bytemuck::cast_slice(&[position.x, position.y, position.z, 0.0_f32]),
+        );
+
+        self.line_batch.clear();
+        let field_corners = [(-1.0, -1.0), (1.0, -1.0), (1.0, 1.0), (-1.0, 1.0)]
+            .map(|(x, z)| Vec3::new(x * FIELD_HALF_SIZE, 0.0, z * FIELD_HALF_SIZE));
+        self.line_batch.polygon(&field_corners, FIELD_BOUNDS_COLOR);
+        // the near end collapses to the camera position with a perspective projection
+        self.line_batch.frustum(
+            &main_camera.frustum_corners(0.0),
+            &main_camera.frustum_corners(self.settings.frustum_distance),
+            FRUSTUM_COLOR,
+        );
+        self.lines.update(queue, view_proj, &self.line_batch);
+    }
+
+    /// Draws the overlay lines and copies the minimap into the top right corner of `target`
+    pub fn composite(&self, encoder: &mut wgpu::CommandEncoder, target: &HdrTarget) {
+        self.lines.run(encoder, &self.hdr.view);
+
+        let target_size = target.texture.size();
+        if target_size.width < MINIMAP_SIZE + MINIMAP_MARGIN
+            || target_size.height < MINIMAP_SIZE + MINIMAP_MARGIN
+        {
+            return;
+        }
+        encoder.copy_texture_to_texture(
+            self.hdr.texture.as_image_copy(),
+            wgpu::TexelCopyTextureInfo {
+                origin: wgpu::Origin3d {
+                    x: target_size.width - MINIMAP_SIZE - MINIMAP_MARGIN,
+                    y: MINIMAP_MARGIN,
+                    z: 0,
+                },
+                ..target.texture.as_image_copy()
+            },
+            self.hdr.texture.size(),
+        );
+    }
+}
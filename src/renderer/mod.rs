@@ -2,23 +2,63 @@ pub mod pipeline;
 pub mod compute;
 pub mod depth;
 pub mod ground;
+pub mod flow_map;
+pub mod shadow;
+pub mod sky;
+pub mod hdr;
+pub mod tonemap;
+pub mod exposure;
+pub mod taa;
+pub mod lines;
+pub mod minimap;
+pub mod debug_view;
+pub mod debug_lines;
+pub mod stats;
+pub mod profiler;
+pub mod ui;
 
 use crate::grass::Grass;
+use crate::grass::blade::BladeSettings;
+use profiler::GpuPass;
 use crate::grass::mesh::GrassMesh;
-use crate::camera::Camera;
+use crate::grass::species::GrassMaterial;
+use crate::camera::{Camera, Projection};
 use crate::camera::controller::CameraController;
+use crate::camera::path::{CameraPath, PathPlayer, PathRecorder};
 use crate::config::{
-    BLADE_HEIGHT, CAMERA_INITIAL_DISTANCE, GRASS_COUNT, SKY_COLOR
+    CAMERA_INITIAL_DISTANCE, GRASS_COUNT, MAX_SIMULATION_STEPS, SIMULATION_TIMESTEP,
 };
+use crate::wind::{WindField, WindUniforms};
+use crate::wind::emitter::{GpuWindEmitter, WindEmitters, MAX_WIND_EMITTERS};
+use crate::wind::flow_map::{FlowMapImage, FlowMapSettings};
+use std::path::Path;
+use crate::clock::SimulationClock;
+use crate::lighting::Lighting;
+use crate::lighting::day_cycle::DayCycle;
 use wgpu::util::DeviceExt;
-use std::time::Instant;
+
+/// Features used when the adapter has them
+const OPTIONAL_FEATURES: wgpu::Features = wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
+    .union(wgpu::Features::POLYGON_MODE_LINE)
+    .union(wgpu::Features::TIMESTAMP_QUERY)
+    .union(wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS)
+    .union(wgpu::Features::TIMESTAMP_QUERY_INSIDE_PASSES);
+
+/// Where the tonemapped frames end up
+enum FrameTarget {
+    Surface(wgpu::Surface<'static>),
+    /// Offscreen colour texture of a headless renderer, never read back
+    Texture(wgpu::Texture),
+}
 
 pub struct Renderer {
-    surface: wgpu::Surface<'static>,
+    target: FrameTarget,
     device: wgpu::Device,
     queue: wgpu::Queue,
     config: wgpu::SurfaceConfiguration,
     size: winit::dpi::PhysicalSize<u32>,
+    sample_count: u32,
+    adapter_name: String,
     
     // Rendering resources
     pipeline: pipeline::Pipeline,
@@ -31,35 +71,126 @@ pub struct Renderer {
     camera_buffer: wgpu::Buffer,
     camera_position_buffer: wgpu::Buffer,
     camera_controller: CameraController,
+    /// Orthographic view straight down on the orbit focus
+    top_down: bool,
+    /// Drives the camera instead of the controller while playing
+    path_player: Option<PathPlayer>,
+    path_recorder: Option<PathRecorder>,
+    /// Unjittered camera and last frame's camera, for motion vectors
+    temporal_buffer: wgpu::Buffer,
+    previous_view_proj: glam::Mat4,
     
     // Compute
     compute: compute::ComputeResources,
+    flow_map: flow_map::FlowMap,
+    flow_map_settings: FlowMapSettings,
     
     // Uniforms
     render_bind_group: wgpu::BindGroup,
+    wind: WindField,
+    blade: BladeSettings,
     wind_uniform_buffer: wgpu::Buffer,
-    start_time: Instant,
+    wind_emitters: WindEmitters,
+    emitter_buffer: wgpu::Buffer,
+    clock: SimulationClock,
+    simulation_accumulator: f32,
+    lighting: Lighting,
+    lighting_buffer: wgpu::Buffer,
+    material: GrassMaterial,
+    material_buffer: wgpu::Buffer,
+    debug_view: debug_view::DebugView,
+    debug_buffer: wgpu::Buffer,
+    /// Draws the triangle edges of the grass and ground
+    wireframe: bool,
+    debug_lines: debug_lines::DebugLines,
+    /// Drives `lighting` while enabled
+    day_cycle: Option<DayCycle>,
 
     // Ground
     ground: ground::Ground,
+
+    // Shadows
+    shadow: shadow::ShadowMap,
+
+    // Sky
+    sky: sky::Sky,
+
+    // Post processing
+    hdr: hdr::HdrTarget,
+    velocity: hdr::HdrTarget,
+    taa: taa::Taa,
+    tonemap: tonemap::Tonemap,
+    auto_exposure: exposure::AutoExposure,
+    minimap: minimap::Minimap,
+
+    // Overlay
+    ui: ui::UiRenderer,
+    frame_stats: stats::FrameStats,
+    /// GPU pass times, None without `TIMESTAMP_QUERY`
+    gpu_profiler: Option<profiler::GpuProfiler>,
 }
 
 impl Renderer {
-    pub async fn new(window: &'static winit::window::Window) -> Self {
-        let size = window.inner_size();
-        
-        // Initialize WGPU
-        let (_instance, surface, adapter) = Self::init_wgpu(window).await;
+    /// `sample_count` is the requested MSAA level, the highest supported level up to it is used.
+    /// The grass is scattered with `seed`, or randomly without one.
+    /// `shadow_settings` sizes the shadow map, which is fixed for the lifetime of the renderer.
+    pub async fn new(
+        window: &'static winit::window::Window,
+        sample_count: u32,
+        seed: Option<u64>,
+        shadow_settings: shadow::ShadowSettings,
+    ) -> Self {
+        let instance = Self::create_instance();
+        let surface = instance.create_surface(window).unwrap();
+        let adapter = Self::request_adapter(&instance, Some(&surface)).await;
+        Self::from_adapter(adapter, Some(surface), window.inner_size(), sample_count, seed, shadow_settings).await
+    }
+
+    /// Renderer drawing into an offscreen texture of `size`, without a window
+    pub async fn new_headless(
+        size: winit::dpi::PhysicalSize<u32>,
+        sample_count: u32,
+        seed: Option<u64>,
+        shadow_settings: shadow::ShadowSettings,
+    ) -> Self {
+        let instance = Self::create_instance();
+        let adapter = Self::request_adapter(&instance, None).await;
+        Self::from_adapter(adapter, None, size, sample_count, seed, shadow_settings).await
+    }
+
+    async fn from_adapter(
+        adapter: wgpu::Adapter,
+        surface: Option<wgpu::Surface<'static>>,
+        size: winit::dpi::PhysicalSize<u32>,
+        sample_count: u32,
+        seed: Option<u64>,
+        shadow_settings: shadow::ShadowSettings,
+    ) -> Self {
         Self::log_adapter_info(&adapter);
         
         let (device, queue) = adapter
-            .request_device(&Default::default())
+            .request_device(&wgpu::DeviceDescriptor {
+                label: None,
+                required_features: adapter.features() & OPTIONAL_FEATURES,
+                ..Default::default()
+            })
             .await
             .unwrap();
+        let sample_count = Self::supported_sample_count(&adapter, &device, sample_count);
+        log::info!("MSAA samples: {}", sample_count);
         
         // Configure surface
-        let config = Self::create_surface_config(&surface, &adapter, size);
-        surface.configure(&device, &config);
+        let (target, config) = match surface {
+            Some(surface) => {
+                let config = Self::create_surface_config(&surface, &adapter, size);
+                surface.configure(&device, &config);
+                (FrameTarget::Surface(surface), config)
+            }
+            None => {
+                let config = Self::create_headless_config(size);
+                (FrameTarget::Texture(Self::create_headless_texture(&device, &config)), config)
+            }
+        };
 
         // Create camera
         let aspect = size.width as f32 / size.height as f32;
@@ -71,23 +202,63 @@ impl Renderer {
         let camera_controller = CameraController::new(CAMERA_INITIAL_DISTANCE, glam::Vec3::ZERO);
         let camera_buffer = Self::create_camera_buffer(&device, &camera);
         let camera_position_buffer = Self::create_camera_position_buffer(&device, &camera);
+        let previous_view_proj = camera.build_unjittered_view_projection_matrix();
+        let temporal_buffer = Self::create_temporal_buffer(&device, previous_view_proj);
 
         // Create uniforms
-        let wind_uniform_buffer = Self::create_wind_buffer(&device);
+        let wind = WindField::default();
+        let wind_uniform_buffer = Self::create_wind_buffer(&device, &wind);
+        let emitter_buffer = Self::create_emitter_buffer(&device);
+        let lighting = Lighting::default();
+        let lighting_buffer = Self::create_lighting_buffer(&device, &lighting);
+        let material = GrassMaterial::default();
+        let material_buffer = Self::create_material_buffer(&device, &material);
+        let debug_buffer = Self::create_debug_buffer(&device);
 
         // Create bind groups
         let render_bind_group_layout = Self::create_render_bind_group_layout(&device);
+        let shared_render_entries = Self::shared_render_entries(
+            &wind_uniform_buffer,
+            &camera_position_buffer,
+            &lighting_buffer,
+            &temporal_buffer,
+            &material_buffer,
+            &debug_buffer,
+        );
         let render_bind_group = Self::create_render_bind_group(
             &device,
             &render_bind_group_layout,
             &camera_buffer,
-            &wind_uniform_buffer,
-            &camera_position_buffer,
+            &shared_render_entries,
+        );
+
+        let minimap = minimap::Minimap::new(
+            &device,
+            sample_count,
+            &render_bind_group_layout,
+            &shared_render_entries,
+        );
+        let debug_lines = debug_lines::DebugLines::new(&device);
+
+        // Create shadow map
+        let shadow_bind_group_layout = shadow::ShadowMap::create_bind_group_layout(&device);
+        let shadow = shadow::ShadowMap::new(
+            &device,
+            shadow_settings,
+            &shadow_bind_group_layout,
+            &render_bind_group_layout,
+            &shared_render_entries,
         );
 
         // Create pipeline and grass
-        let pipeline = pipeline::Pipeline::new(&device, config.format, &render_bind_group_layout);
-        let grass = Grass::new(&device, GRASS_COUNT);
+        let pipeline = pipeline::Pipeline::new(
+            &device,
+            hdr::HDR_FORMAT,
+            sample_count,
+            &render_bind_group_layout,
+            &shadow_bind_group_layout,
+        );
+        let grass = Grass::new(&device, GRASS_COUNT, seed);
         let grass_mesh = GrassMesh::new(&device);
 
         // Create compute resources
@@ -96,20 +267,54 @@ impl Renderer {
             grass.get_positions(),
             grass.get_instance_buffer(),
             &wind_uniform_buffer,
+            &emitter_buffer,
+        );
+        let flow_map = flow_map::FlowMap::new(
+            &device,
+            &queue,
+            &compute.flow_map_layout,
+            &FlowMapImage::neutral(),
         );
 
         // Create depth texture
-        let depth = depth::DepthTexture::new(&device, config.width, config.height);
+        let depth = depth::DepthTexture::new(&device, config.width, config.height, sample_count);
 
         // Create ground
-        let ground = ground::Ground::new(&device, config.format, &render_bind_group_layout);
+        let ground = ground::Ground::new(
+            &device,
+            hdr::HDR_FORMAT,
+            sample_count,
+            &render_bind_group_layout,
+            &shadow_bind_group_layout,
+        );
+
+        // Create sky
+        let sky = sky::Sky::new(&device, hdr::HDR_FORMAT, sample_count);
+
+        // Create HDR target and post processing
+        let hdr = hdr::HdrTarget::new(&device, config.width, config.height, sample_count);
+        let velocity = hdr::HdrTarget::velocity(&device, config.width, config.height, sample_count);
+        let taa = taa::Taa::new(&device, &hdr, &velocity);
+        let auto_exposure = exposure::AutoExposure::new(&device, &hdr.view);
+        let tonemap = tonemap::Tonemap::new(
+            &device,
+            config.format,
+            &hdr.view,
+            &auto_exposure.luminance_buffer,
+        );
+
+        let ui = ui::UiRenderer::new(&device, config.format);
+        let gpu_profiler = profiler::GpuProfiler::new(&device, &queue);
+        log::info!("GPU timestamps: {}", if gpu_profiler.is_some() { "supported" } else { "unsupported" });
 
         Self {
-            surface,
+            target,
             device,
             queue,
             config,
             size,
+            sample_count,
+            adapter_name: adapter.get_info().name,
             pipeline,
             grass,
             grass_mesh,
@@ -118,33 +323,61 @@ impl Renderer {
             camera_buffer,
             camera_position_buffer,
             camera_controller,
+            top_down: false,
+            path_player: None,
+            path_recorder: None,
+            temporal_buffer,
+            previous_view_proj,
             compute,
+            flow_map,
+            flow_map_settings: FlowMapSettings::default(),
             render_bind_group,
+            wind,
+            blade: BladeSettings::default(),
             wind_uniform_buffer,
-            start_time: Instant::now(),
+            wind_emitters: WindEmitters::default(),
+            emitter_buffer,
+            clock: SimulationClock::new(),
+            simulation_accumulator: 0.0,
+            lighting,
+            lighting_buffer,
+            material,
+            material_buffer,
+            debug_view: debug_view::DebugView::default(),
+            debug_buffer,
+            wireframe: false,
+            debug_lines,
+            day_cycle: None,
             ground,
+            shadow,
+            sky,
+            hdr,
+            velocity,
+            taa,
+            tonemap,
+            auto_exposure,
+            minimap,
+            ui,
+            frame_stats: stats::FrameStats::default(),
+            gpu_profiler,
         }
     }
 
-    async fn init_wgpu(
-        window: &'static winit::window::Window,
-    ) -> (wgpu::Instance, wgpu::Surface<'static>, wgpu::Adapter) {
-        let instance = wgpu::Instance::new(&wgpu::InstanceDescriptor {
+    fn create_instance() -> wgpu::Instance {
+        wgpu::Instance::new(&wgpu::InstanceDescriptor {
             backends: wgpu::Backends::all(),
             ..Default::default()
-        });
-        
-        let surface = instance.create_surface(window).unwrap();
-        
-        let adapter = instance
+        })
+    }
+
+    async fn request_adapter(instance: &wgpu::Instance, compatible_surface: Option<&wgpu::Surface<'_>>) -> wgpu::Adapter {
+        instance
             .request_adapter(&wgpu::RequestAdapterOptions {
-                compatible_surface: Some(&surface),
+                compatible_surface,
                 ..Default::default()
             })
             .await
-            .unwrap();
-
-        (instance, surface, adapter)
+            .unwrap()
     }
 
     fn log_adapter_info(adapter: &wgpu::Adapter) {
@@ -156,6 +389,28 @@ impl Renderer {
         println!("Driver Info: {}", info.driver_info);
     }
 
+    /// Highest MSAA level up to `requested` usable for the HDR, velocity and depth targets
+    fn supported_sample_count(adapter: &wgpu::Adapter, device: &wgpu::Device, requested: u32) -> u32 {
+        // without adapter specific format features only 1 and 4 samples are allowed
+        let adapter_specific = device
+            .features()
+            .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES);
+        [8, 4, 2, 1]
+            .into_iter()
+            .filter(|&count| count <= requested && (adapter_specific || count == 4 || count == 1))
+            .find(|&count| {
+                [hdr::HDR_FORMAT, hdr::VELOCITY_FORMAT, depth::DEPTH_FORMAT]
+                    .iter()
+                    .all(|&format| {
+                        adapter
+                            .get_texture_format_features(format)
+                            .flags
+                            .sample_count_supported(count)
+                    })
+            })
+            .unwrap_or(1)
+    }
+
     fn create_surface_config(
         surface: &wgpu::Surface,
         adapter: &wgpu::Adapter,
@@ -181,6 +436,37 @@ impl Renderer {
         }
     }
 
+    /// Same layout as a surface configuration so the rest of the renderer does not care
+    fn create_headless_config(size: winit::dpi::PhysicalSize<u32>) -> wgpu::SurfaceConfiguration {
+        wgpu::SurfaceConfiguration {
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            width: size.width.max(1),
+            height: size.height.max(1),
+            present_mode: wgpu::PresentMode::Fifo,
+            alpha_mode: wgpu::CompositeAlphaMode::Opaque,
+            view_formats: vec![],
+            desired_maximum_frame_latency: 2,
+        }
+    }
+
+    fn create_headless_texture(device: &wgpu::Device, config: &wgpu::SurfaceConfiguration) -> wgpu::Texture {
+        device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Headless Frame Texture"),
+            size: wgpu::Extent3d {
+                width: config.width,
+                height: config.height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: config.format,
+            usage: config.usage,
+            view_formats: &[],
+        })
+    }
+
     fn create_camera_buffer(device: &wgpu::Device, camera: &Camera) -> wgpu::Buffer {
         device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Camera Buffer"),
@@ -198,21 +484,59 @@ impl Renderer {
         })
     }
 
-    fn create_wind_buffer(device: &wgpu::Device) -> wgpu::Buffer {
-        use crate::config::{WIND_STRENGTH, WIND_ANGLE, GRASS_COUNT};
-        
+    fn create_temporal_buffer(device: &wgpu::Device, view_proj: glam::Mat4) -> wgpu::Buffer {
+        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Temporal Buffer"),
+            contents: bytemuck::cast_slice(&[view_proj.to_cols_array(); 2]),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        })
+    }
+
+    fn create_wind_buffer(device: &wgpu::Device, wind: &WindField) -> wgpu::Buffer {
+        let uniforms = WindUniforms::new(
+            wind,
+            &BladeSettings::default(),
+            0.0,
+            GRASS_COUNT as u32,
+            0,
+            &FlowMapSettings::default(),
+        );
         device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
             label: Some("Wind Uniform Buffer"),
-            contents: bytemuck::cast_slice(&[
-                WIND_STRENGTH,
-                0.0_f32,
-                WIND_ANGLE,
-                GRASS_COUNT as f32,
-                BLADE_HEIGHT,
-                0.0_f32,
-                0.0_f32,
-                0.0_f32,
-                ]),
+            contents: bytemuck::bytes_of(&uniforms),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        })
+    }
+
+    fn create_emitter_buffer(device: &wgpu::Device) -> wgpu::Buffer {
+        device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Wind Emitter Buffer"),
+            size: (MAX_WIND_EMITTERS * std::mem::size_of::<GpuWindEmitter>()) as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        })
+    }
+
+    fn create_lighting_buffer(device: &wgpu::Device, lighting: &Lighting) -> wgpu::Buffer {
+        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Lighting Uniform Buffer"),
+            contents: bytemuck::bytes_of(&lighting.to_uniforms()),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        })
+    }
+
+    fn create_material_buffer(device: &wgpu::Device, material: &GrassMaterial) -> wgpu::Buffer {
+        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Material Buffer"),
+            contents: bytemuck::bytes_of(&material.to_uniforms()),
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+        })
+    }
+
+    fn create_debug_buffer(device: &wgpu::Device) -> wgpu::Buffer {
+        device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+            label: Some("Debug Buffer"),
+            contents: bytemuck::bytes_of(&debug_view::DebugView::default().to_uniforms(false)),
             usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
         })
     }
@@ -251,6 +575,47 @@ impl Renderer {
                     },
                     count: None,
                 },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    // the grass shadow pass thickens blades towards the sun
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::VERTEX,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 5,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 6,
+                    visibility: wgpu::ShaderStages::VERTEX | wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
             ],
         })
     }
@@ -259,67 +624,392 @@ impl Renderer {
         device: &wgpu::Device,
         layout: &wgpu::BindGroupLayout,
         camera_buffer: &wgpu::Buffer,
-        wind_buffer: &wgpu::Buffer,
-        camera_position_buffer: &wgpu::Buffer,
+        shared_entries: &[wgpu::BindGroupEntry],
     ) -> wgpu::BindGroup {
+        let mut entries = vec![wgpu::BindGroupEntry {
+            binding: 0,
+            resource: camera_buffer.as_entire_binding(),
+        }];
+        entries.extend_from_slice(shared_entries);
+
         device.create_bind_group(&wgpu::BindGroupDescriptor {
             label: Some("Render Bind Group"),
             layout,
-            entries: &[
-                wgpu::BindGroupEntry {
-                    binding: 0,
-                    resource: camera_buffer.as_entire_binding(),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 1,
-                    resource: wind_buffer.as_entire_binding(),
-                },
-                wgpu::BindGroupEntry {
-                    binding: 2,
-                    resource: camera_position_buffer.as_entire_binding(),
-                },
-            ],
+            entries: &entries,
         })
     }
+
+    /// Render bind group entries besides the view projection, shared with the shadow passes
+    fn shared_render_entries<'a>(
+        wind_buffer: &'a wgpu::Buffer,
+        camera_position_buffer: &'a wgpu::Buffer,
+        lighting_buffer: &'a wgpu::Buffer,
+        temporal_buffer: &'a wgpu::Buffer,
+        material_buffer: &'a wgpu::Buffer,
+        debug_buffer: &'a wgpu::Buffer,
+    ) -> [wgpu::BindGroupEntry<'a>; 6] {
+        [
+            wgpu::BindGroupEntry {
+                binding: 1,
+                resource: wind_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 2,
+                resource: camera_position_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 3,
+                resource: lighting_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 4,
+                resource: temporal_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 5,
+                resource: material_buffer.as_entire_binding(),
+            },
+            wgpu::BindGroupEntry {
+                binding: 6,
+                resource: debug_buffer.as_entire_binding(),
+            },
+        ]
+    }
     
     pub fn resize(&mut self, new_size: winit::dpi::PhysicalSize<u32>) {
         if new_size.width > 0 && new_size.height > 0 {
             self.size = new_size;
             self.config.width = new_size.width;
             self.config.height = new_size.height;
-            self.surface.configure(&self.device, &self.config);
+            self.configure_target();
             self.camera.aspect = new_size.width as f32 / new_size.height as f32;
             
             // Recreate depth texture
-            self.depth = depth::DepthTexture::new(&self.device, new_size.width, new_size.height);
+            self.depth = depth::DepthTexture::new(
+                &self.device,
+                new_size.width,
+                new_size.height,
+                self.sample_count,
+            );
+
+            // Recreate HDR target
+            self.hdr = hdr::HdrTarget::new(
+                &self.device,
+                new_size.width,
+                new_size.height,
+                self.sample_count,
+            );
+            self.velocity = hdr::HdrTarget::velocity(
+                &self.device,
+                new_size.width,
+                new_size.height,
+                self.sample_count,
+            );
+            self.taa.set_targets(&self.device, &self.hdr, &self.velocity);
+            self.auto_exposure.set_hdr_view(&self.device, &self.hdr.view);
+            self.tonemap.set_hdr_view(
+                &self.device,
+                &self.hdr.view,
+                &self.auto_exposure.luminance_buffer,
+            );
         }
     }
     
+    pub fn camera_mut(&mut self) -> &mut Camera {
+        &mut self.camera
+    }
+
     pub fn camera_controller_mut(&mut self) -> &mut CameraController {
         &mut self.camera_controller
     }
 
+    pub fn is_top_down(&self) -> bool {
+        self.top_down
+    }
+
+    /// Switches between the orthographic top-down view and the perspective one
+    pub fn set_top_down(&mut self, top_down: bool) {
+        self.top_down = top_down;
+        if !top_down {
+            self.camera.projection = Projection::Perspective;
+        }
+    }
+
+    pub fn play_camera_path(&mut self, path: CameraPath) {
+        self.path_player = Some(PathPlayer::new(path));
+    }
+
+    /// Plays `path` after `delay` seconds on its first keyframe, then keeps the camera
+    /// on the last keyframe until another path is played
+    pub fn hold_camera_path(&mut self, path: CameraPath, delay: f32) {
+        self.path_player = Some(PathPlayer::held(path, delay));
+    }
+
+    pub fn is_playing_camera_path(&self) -> bool {
+        self.path_player.is_some()
+    }
+
+    pub fn start_recording_camera_path(&mut self) {
+        self.path_recorder = Some(PathRecorder::default());
+    }
+
+    /// Returns the recorded path, `None` if nothing was being recorded
+    pub fn stop_recording_camera_path(&mut self) -> Option<CameraPath> {
+        self.path_recorder.take().map(PathRecorder::finish)
+    }
+
+    pub fn lighting_mut(&mut self) -> &mut Lighting {
+        &mut self.lighting
+    }
+
+    pub fn grass_material_mut(&mut self) -> &mut GrassMaterial {
+        &mut self.material
+    }
+
+    pub fn debug_view_mut(&mut self) -> &mut debug_view::DebugView {
+        &mut self.debug_view
+    }
+
+    pub fn is_wireframe(&self) -> bool {
+        self.wireframe
+    }
+
+    pub fn set_wireframe(&mut self, wireframe: bool) {
+        self.wireframe = wireframe;
+    }
+
+    /// Wireframe drawn by the shaders, used when the adapter lacks `POLYGON_MODE_LINE`
+    fn barycentric_wireframe(&self) -> bool {
+        self.wireframe && !self.device.features().contains(wgpu::Features::POLYGON_MODE_LINE)
+    }
+
+    pub fn debug_line_settings_mut(&mut self) -> &mut debug_lines::DebugLineSettings {
+        &mut self.debug_lines.settings
+    }
+
+    pub fn day_cycle_mut(&mut self) -> Option<&mut DayCycle> {
+        self.day_cycle.as_mut()
+    }
+
+    /// Enables or disables the day cycle, disabling it restores the default lighting
+    pub fn set_day_cycle(&mut self, day_cycle: Option<DayCycle>) {
+        if day_cycle.is_none() {
+            self.lighting = Lighting::default();
+        }
+        self.day_cycle = day_cycle;
+    }
+
+    pub fn sky_settings_mut(&mut self) -> &mut sky::SkySettings {
+        &mut self.sky.settings
+    }
+
+    pub fn tonemap_settings_mut(&mut self) -> &mut tonemap::TonemapSettings {
+        &mut self.tonemap.settings
+    }
+
+    pub fn taa_settings_mut(&mut self) -> &mut taa::TaaSettings {
+        &mut self.taa.settings
+    }
+
+    pub fn minimap_settings_mut(&mut self) -> &mut minimap::MinimapSettings {
+        &mut self.minimap.settings
+    }
+
+    /// Drawn on top of the next rendered frame
+    pub fn set_ui_frame(&mut self, frame: ui::UiFrame) {
+        self.ui.set_frame(frame);
+    }
+
+    pub fn frame_stats(&self) -> &stats::FrameStats {
+        &self.frame_stats
+    }
+
+    pub fn gpu_profiler(&self) -> Option<&profiler::GpuProfiler> {
+        self.gpu_profiler.as_ref()
+    }
+
+    pub fn gpu_profiler_mut(&mut self) -> Option<&mut profiler::GpuProfiler> {
+        self.gpu_profiler.as_mut()
+    }
+
+    pub fn adapter_name(&self) -> &str {
+        &self.adapter_name
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    pub fn size(&self) -> winit::dpi::PhysicalSize<u32> {
+        self.size
+    }
+
+    pub fn blade_count(&self) -> u32 {
+        self.grass.instance_count()
+    }
+
+    pub fn clock_mut(&mut self) -> &mut SimulationClock {
+        &mut self.clock
+    }
+
+    pub fn wind_mut(&mut self) -> &mut WindField {
+        &mut self.wind
+    }
+
+    pub fn blade_settings_mut(&mut self) -> &mut BladeSettings {
+        &mut self.blade
+    }
+
+    pub fn wind_emitters_mut(&mut self) -> &mut WindEmitters {
+        &mut self.wind_emitters
+    }
+
+    /// Loads a flow map image covering the field and enables it with the given settings
+    pub fn load_flow_map(&mut self, path: &Path, settings: FlowMapSettings) -> Result<(), image::ImageError> {
+        let image = FlowMapImage::load(path)?;
+        self.flow_map = flow_map::FlowMap::new(
+            &self.device,
+            &self.queue,
+            &self.compute.flow_map_layout,
+            &image,
+        );
+        self.flow_map_settings = settings;
+        Ok(())
+    }
+
+    fn configure_target(&mut self) {
+        match &mut self.target {
+            FrameTarget::Surface(surface) => surface.configure(&self.device, &self.config),
+            FrameTarget::Texture(texture) => *texture = Self::create_headless_texture(&self.device, &self.config),
+        }
+    }
+
+    /// Without vsync frames are presented as soon as they are done, ignored by headless renderers
+    pub fn set_vsync(&mut self, vsync: bool) {
+        self.config.present_mode = if vsync { wgpu::PresentMode::Fifo } else { wgpu::PresentMode::AutoNoVsync };
+        if matches!(self.target, FrameTarget::Surface(_)) {
+            self.configure_target();
+        }
+    }
+
+    /// Blocks until the submitted frames are done on the GPU and collects their GPU times
+    pub fn wait_for_gpu(&mut self) {
+        let _ = self.device.poll(wgpu::PollType::wait_indefinitely());
+        if let Some(gpu_profiler) = &mut self.gpu_profiler {
+            gpu_profiler.collect(&self.device);
+        }
+    }
+
     pub fn render(&mut self) {
+        let delta_time = self.clock.tick();
+        if let Some(gpu_profiler) = &mut self.gpu_profiler {
+            gpu_profiler.collect(&self.device);
+        }
+
+        if let Some(day_cycle) = &mut self.day_cycle {
+            day_cycle.update(delta_time);
+            day_cycle.apply(&mut self.lighting);
+        }
         self.update_camera();
+        self.update_lighting_uniforms();
+        self.update_debug_uniforms();
+        // debug views keep their colours regardless of the scene brightness
+        self.tonemap.update(&self.queue, self.debug_view != debug_view::DebugView::None);
+        self.wind_emitters.update(delta_time);
         self.update_wind_uniforms();
+        let simulation_steps = self.advance_simulation(delta_time);
+        self.frame_stats.record_frame(simulation_steps);
 
-        let output = self.surface.get_current_texture().unwrap();
-        let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
+        let (output, view) = match &self.target {
+            FrameTarget::Surface(surface) => {
+                let output = surface.get_current_texture().unwrap();
+                let view = output.texture.create_view(&wgpu::TextureViewDescriptor::default());
+                (Some(output), view)
+            }
+            FrameTarget::Texture(texture) => (None, texture.create_view(&wgpu::TextureViewDescriptor::default())),
+        };
         
         let mut encoder = self.device.create_command_encoder(&wgpu::CommandEncoderDescriptor {
             label: Some("Render Encoder"),
         });
         
-        self.run_compute_pass(&mut encoder);
-        self.run_render_pass(&mut encoder, &view);
+        self.grass.store_previous(&mut encoder);
+        self.run_compute_pass(&mut encoder, simulation_steps);
+        self.run_shadow_passes(&mut encoder);
+        self.run_render_pass(&mut encoder);
+        if self.taa.settings.enabled {
+            self.taa.run(&mut encoder, &self.queue, &self.hdr);
+        } else {
+            self.taa.invalidate_history();
+        }
+        self.debug_lines.run(&mut encoder, &self.hdr);
+        if self.minimap.settings.enabled {
+            self.run_minimap_pass(&mut encoder);
+            self.minimap.composite(&mut encoder, &self.hdr);
+        }
+        if self.tonemap.settings.auto_exposure && self.debug_view == debug_view::DebugView::None {
+            self.auto_exposure.run(
+                &mut encoder,
+                &self.queue,
+                (self.config.width, self.config.height),
+                // keeps adapting while the simulation is paused
+                self.clock.real_delta(),
+            );
+        }
+        self.run_tonemap_pass(&mut encoder, &view);
+        self.ui.run(
+            &self.device,
+            &self.queue,
+            &mut encoder,
+            &view,
+            [self.config.width, self.config.height],
+        );
         
+        if let Some(gpu_profiler) = &mut self.gpu_profiler {
+            gpu_profiler.resolve(&mut encoder);
+        }
+
         self.queue.submit(std::iter::once(encoder.finish()));
-        output.present();
+        if let Some(gpu_profiler) = &mut self.gpu_profiler {
+            gpu_profiler.after_submit();
+        }
+        if let Some(output) = output {
+            output.present();
+        }
     }
 
     fn update_camera(&mut self) {
-        let camera_pos = self.camera_controller.calculate_position();
-        self.camera.update_position(camera_pos, self.camera_controller.target);
+        let delta_time = self.clock.real_delta();
+        match &mut self.path_player {
+            Some(player) => {
+                player.update(delta_time, &mut self.camera);
+                if player.is_finished() {
+                    self.path_player = None;
+                }
+            }
+            None => {
+                self.camera_controller.update(delta_time);
+                let camera_pos = self.camera_controller.calculate_position();
+                self.camera.update_position(camera_pos, self.camera_controller.look_target());
+            }
+        }
+        if self.top_down {
+            // framed like the perspective view at the orbit distance
+            let focus = self.camera_controller.focus_point();
+            let distance = self.camera_controller.distance;
+            self.camera.projection = Projection::Orthographic {
+                height: 2.0 * distance * (self.camera.fovy * 0.5).tan(),
+            };
+            self.camera.update_position(focus + glam::Vec3::Y * distance, focus);
+        }
+        if let Some(recorder) = &mut self.path_recorder {
+            recorder.record(delta_time, &self.camera);
+        }
+        self.camera.jitter = if self.taa.settings.enabled {
+            self.taa.next_jitter(self.config.width, self.config.height)
+        } else {
+            glam::Vec2::ZERO
+        };
         
         let camera_matrix = self.camera.build_view_projection_matrix();
         self.queue.write_buffer(
@@ -334,86 +1024,351 @@ impl Renderer {
             0,
             bytemuck::cast_slice(&camera_pos_data),
         );
+
+        let view_proj = self.camera.build_unjittered_view_projection_matrix();
+        self.queue.write_buffer(
+            &self.temporal_buffer,
+            0,
+            bytemuck::cast_slice(&[view_proj.to_cols_array(), self.previous_view_proj.to_cols_array()]),
+        );
+
+        self.shadow.update(&self.queue, &self.camera, self.lighting.sun_direction);
+        self.sky.update(
+            &self.queue,
+            &self.camera,
+            &self.lighting,
+            self.previous_view_proj,
+            self.tonemap.settings.tonemapper == tonemap::Tonemapper::Clamp,
+        );
+        self.previous_view_proj = view_proj;
+
+        if self.minimap.settings.enabled {
+            self.minimap.update(&self.queue, &self.camera);
+        }
+        self.debug_lines.update(&self.queue, &self.camera, &self.wind, self.blade.height);
+    }
+
+    fn update_lighting_uniforms(&mut self) {
+        self.queue.write_buffer(
+            &self.lighting_buffer,
+            0,
+            bytemuck::bytes_of(&self.lighting.to_uniforms()),
+        );
+        self.queue.write_buffer(
+            &self.material_buffer,
+            0,
+            bytemuck::bytes_of(&self.material.to_uniforms()),
+        );
+    }
+
+    fn update_debug_uniforms(&mut self) {
+        self.queue.write_buffer(
+            &self.debug_buffer,
+            0,
+            bytemuck::bytes_of(&self.debug_view.to_uniforms(self.barycentric_wireframe())),
+        );
     }
 
     fn update_wind_uniforms(&mut self) {
-        use crate::config::{WIND_STRENGTH, WIND_ANGLE};
-        
-        let elapsed = self.start_time.elapsed().as_secs_f32();
-        let wind_data = [
-            WIND_STRENGTH,
-            elapsed,
-            WIND_ANGLE,
-            self.grass.instance_count() as f32,
-            BLADE_HEIGHT,
-            0.0_f32,
-            0.0_f32,
-            0.0_f32,
-        ];
+        let emitters = self.wind_emitters.gpu_data();
+        let uniforms = WindUniforms::new(
+            &self.wind,
+            &self.blade,
+            self.clock.time(),
+            self.grass.instance_count(),
+            emitters.len() as u32,
+            &self.flow_map_settings,
+        );
         self.queue.write_buffer(
             &self.wind_uniform_buffer,
             0,
-            bytemuck::cast_slice(&wind_data),
+            bytemuck::bytes_of(&uniforms),
         );
+        if !emitters.is_empty() {
+            self.queue.write_buffer(&self.emitter_buffer, 0, bytemuck::cast_slice(&emitters));
+        }
     }
 
-    fn run_compute_pass(&self, encoder: &mut wgpu::CommandEncoder) {
+    /// Accumulates simulation time and returns how many fixed steps to run
+    fn advance_simulation(&mut self, delta_time: f32) -> u32 {
+        self.simulation_accumulator += delta_time;
+
+        // a sped up clock needs proportionally more steps per frame to keep up
+        let max_steps = MAX_SIMULATION_STEPS * self.clock.time_scale().max(1.0).ceil() as u32;
+        let steps = (self.simulation_accumulator / SIMULATION_TIMESTEP) as u32;
+        if steps > max_steps {
+            // too far behind, drop the backlog instead of catching up
+            self.simulation_accumulator = 0.0;
+            return max_steps;
+        }
+        self.simulation_accumulator -= steps as f32 * SIMULATION_TIMESTEP;
+        steps
+    }
+
+    fn run_compute_pass(&mut self, encoder: &mut wgpu::CommandEncoder, steps: u32) {
+        // e.g. while paused, an empty pass would only add zero samples to the compute time
+        if steps == 0 {
+            if let Some(gpu_profiler) = &mut self.gpu_profiler {
+                gpu_profiler.skip_pass(GpuPass::Compute);
+            }
+            return;
+        }
         let mut compute_pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor {
             label: Some("Compute Pass"),
-            timestamp_writes: None,
+            timestamp_writes: self
+                .gpu_profiler
+                .as_ref()
+                .map(|gpu_profiler| gpu_profiler.compute_pass_writes(GpuPass::Compute)),
         });
         
         compute_pass.set_pipeline(&self.compute.pipeline);
-        compute_pass.set_bind_group(0, &self.compute.bind_group, &[]);
+        compute_pass.set_bind_group(1, &self.flow_map.bind_group, &[]);
         
-        let workgroup_count = ((self.grass.instance_count() + 63) / 64) as u32;
-        compute_pass.dispatch_workgroups(workgroup_count, 1, 1);
+        let workgroup_count = self.grass.instance_count().div_ceil(64);
+        for _ in 0..steps {
+            compute_pass.set_bind_group(0, self.compute.next_step_bind_group(), &[]);
+            compute_pass.dispatch_workgroups(workgroup_count, 1, 1);
+        }
+    }
+
+    fn run_shadow_passes(&self, encoder: &mut wgpu::CommandEncoder) {
+        let cascades = self.shadow.cascade_views.iter().zip(&self.shadow.cascade_bind_groups);
+        let last = self.shadow.cascade_views.len().saturating_sub(1);
+        for (i, (view, bind_group)) in cascades.enumerate() {
+            let mut shadow_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("Shadow Pass"),
+                color_attachments: &[],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: wgpu::StoreOp::Store,
+                    }),
+                    stencil_ops: None,
+                }),
+                timestamp_writes: self
+                    .gpu_profiler
+                    .as_ref()
+                    .and_then(|gpu_profiler| gpu_profiler.render_pass_writes(GpuPass::Shadows, i == 0, i == last)),
+                occlusion_query_set: None,
+            });
+
+            shadow_pass.set_pipeline(&self.ground.shadow_pipeline);
+            shadow_pass.set_bind_group(0, bind_group, &[]);
+            shadow_pass.set_vertex_buffer(0, self.ground.vertex_buffer.slice(..));
+            shadow_pass.set_index_buffer(self.ground.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            shadow_pass.draw_indexed(0..self.ground.num_indices, 0, 0..1);
+
+            shadow_pass.set_pipeline(&self.pipeline.shadow_pipeline);
+            shadow_pass.set_vertex_buffer(0, self.grass_mesh.vertex_buffer().slice(..));
+            shadow_pass.set_vertex_buffer(1, self.grass.get_instance_buffer().slice(..));
+            shadow_pass.set_vertex_buffer(2, self.grass.get_previous_instance_buffer().slice(..));
+            shadow_pass.set_index_buffer(
+                self.grass_mesh.index_buffer().slice(..),
+                wgpu::IndexFormat::Uint32,
+            );
+            shadow_pass.draw_indexed(
+                0..self.grass_mesh.num_indices(),
+                0,
+                0..self.grass.instance_count(),
+            );
+        }
     }
 
-    fn run_render_pass(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+    fn run_render_pass(&self, encoder: &mut wgpu::CommandEncoder) {
+        let background = self.background_color();
+        let (view, resolve_target) = self.hdr.attachment();
+        let (velocity_view, velocity_resolve_target) = self.velocity.attachment();
         let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
             label: Some("Render Pass"),
-            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-                view,
-                resolve_target: None,
-                ops: wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(SKY_COLOR),
-                    store: wgpu::StoreOp::Store,
-                },
-                depth_slice: None,
-            })],
+            color_attachments: &[
+                Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: background.x as f64,
+                            g: background.y as f64,
+                            b: background.z as f64,
+                            a: 1.0,
+                        }),
+                        store: wgpu::StoreOp::Store,
+                    },
+                    depth_slice: None,
+                }),
+                Some(wgpu::RenderPassColorAttachment {
+                    view: velocity_view,
+                    resolve_target: velocity_resolve_target,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Store,
+                    },
+                    depth_slice: None,
+                }),
+            ],
             depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
                 view: &self.depth.view,
                 depth_ops: Some(wgpu::Operations {
-                    load: wgpu::LoadOp::Clear(1.0),
+                    load: wgpu::LoadOp::Clear(depth::DEPTH_CLEAR),
                     store: wgpu::StoreOp::Store,
                 }),
                 stencil_ops: None,
             }),
+            timestamp_writes: self
+                .gpu_profiler
+                .as_ref()
+                .and_then(|gpu_profiler| gpu_profiler.render_pass_writes(GpuPass::Scene, true, true)),
+            occlusion_query_set: None,
+        });
+
+        // Render sky
+        if self.debug_view == debug_view::DebugView::None {
+            render_pass.set_pipeline(&self.sky.pipeline);
+            render_pass.set_bind_group(0, &self.sky.bind_group, &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+
+        self.draw_scene(&mut render_pass, &self.render_bind_group, self.gpu_profiler.as_ref());
+    }
+
+    /// Clear colour of the scene passes, black behind the debug views
+    fn background_color(&self) -> glam::Vec3 {
+        match self.debug_view {
+            debug_view::DebugView::None => self.lighting.horizon_color,
+            _ => glam::Vec3::ZERO,
+        }
+    }
+
+    /// Same scene as the main view from the minimap camera, without the sky
+    fn run_minimap_pass(&self, encoder: &mut wgpu::CommandEncoder) {
+        let background = self.background_color();
+        let (view, resolve_target) = self.minimap.hdr.attachment();
+        let (velocity_view, velocity_resolve_target) = self.minimap.velocity.attachment();
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Minimap Pass"),
+            color_attachments: &[
+                Some(wgpu::RenderPassColorAttachment {
+                    view,
+                    resolve_target,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: background.x as f64,
+                            g: background.y as f64,
+                            b: background.z as f64,
+                            a: 1.0,
+                        }),
+                        store: wgpu::StoreOp::Store,
+                    },
+                    depth_slice: None,
+                }),
+                Some(wgpu::RenderPassColorAttachment {
+                    view: velocity_view,
+                    resolve_target: velocity_resolve_target,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::TRANSPARENT),
+                        store: wgpu::StoreOp::Discard,
+                    },
+                    depth_slice: None,
+                }),
+            ],
+            depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                view: &self.minimap.depth.view,
+                depth_ops: Some(wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(depth::DEPTH_CLEAR),
+                    store: wgpu::StoreOp::Discard,
+                }),
+                stencil_ops: None,
+            }),
             timestamp_writes: None,
             occlusion_query_set: None,
         });
 
+        self.draw_scene(&mut render_pass, &self.minimap.render_bind_group, None);
+    }
+
+    /// Draws the ground and grass seen through `render_bind_group`
+    /// `gpu_profiler` times the ground and grass draws, only passed for the main view
+    fn draw_scene(
+        &self,
+        render_pass: &mut wgpu::RenderPass,
+        render_bind_group: &wgpu::BindGroup,
+        gpu_profiler: Option<&profiler::GpuProfiler>,
+    ) {
+        let barycentric_wireframe = self.barycentric_wireframe();
+        if let Some(gpu_profiler) = gpu_profiler {
+            gpu_profiler.begin_inside_pass(render_pass, GpuPass::Ground);
+        }
+
         // Render ground
-        render_pass.set_pipeline(&self.ground.pipeline);
-        render_pass.set_bind_group(0, &self.render_bind_group, &[]);
-        render_pass.set_vertex_buffer(0, self.ground.vertex_buffer.slice(..));
-        render_pass.set_index_buffer(self.ground.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
-        render_pass.draw_indexed(0..self.ground.num_indices, 0, 0..1);
+        let ground_pipeline = match &self.ground.wireframe_pipeline {
+            Some(pipeline) if self.wireframe => pipeline,
+            _ => &self.ground.pipeline,
+        };
+        render_pass.set_pipeline(ground_pipeline);
+        render_pass.set_bind_group(0, render_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.shadow.bind_group, &[]);
+        if barycentric_wireframe {
+            render_pass.set_vertex_buffer(0, self.ground.wireframe_vertex_buffer.slice(..));
+            render_pass.draw(0..self.ground.num_indices, 0..1);
+        } else {
+            render_pass.set_vertex_buffer(0, self.ground.vertex_buffer.slice(..));
+            render_pass.set_index_buffer(self.ground.index_buffer.slice(..), wgpu::IndexFormat::Uint32);
+            render_pass.draw_indexed(0..self.ground.num_indices, 0, 0..1);
+        }
+        if let Some(gpu_profiler) = gpu_profiler {
+            gpu_profiler.end_inside_pass(render_pass, GpuPass::Ground);
+            gpu_profiler.begin_inside_pass(render_pass, GpuPass::Grass);
+        }
 
         // Render grass
-        render_pass.set_pipeline(&self.pipeline.render_pipeline);
-        render_pass.set_bind_group(0, &self.render_bind_group, &[]);
-        render_pass.set_vertex_buffer(0, self.grass_mesh.vertex_buffer().slice(..));
+        let grass_pipeline = match &self.pipeline.wireframe_pipeline {
+            _ if self.debug_view == debug_view::DebugView::Overdraw => &self.pipeline.overdraw_pipeline,
+            Some(pipeline) if self.wireframe => pipeline,
+            _ => &self.pipeline.render_pipeline,
+        };
+        render_pass.set_pipeline(grass_pipeline);
+        render_pass.set_bind_group(0, render_bind_group, &[]);
         render_pass.set_vertex_buffer(1, self.grass.get_instance_buffer().slice(..));
-        render_pass.set_index_buffer(
-            self.grass_mesh.index_buffer().slice(..),
-            wgpu::IndexFormat::Uint32,
-        );
-        render_pass.draw_indexed(
-            0..self.grass_mesh.num_indices(),
-            0,
-            0..self.grass.instance_count(),
-        );
+        render_pass.set_vertex_buffer(2, self.grass.get_previous_instance_buffer().slice(..));
+        if barycentric_wireframe {
+            render_pass.set_vertex_buffer(0, self.grass_mesh.wireframe_vertex_buffer().slice(..));
+            render_pass.draw(0..self.grass_mesh.num_indices(), 0..self.grass.instance_count());
+        } else {
+            render_pass.set_vertex_buffer(0, self.grass_mesh.vertex_buffer().slice(..));
+            render_pass.set_index_buffer(
+                self.grass_mesh.index_buffer().slice(..),
+                wgpu::IndexFormat::Uint32,
+            );
+            render_pass.draw_indexed(
+                0..self.grass_mesh.num_indices(),
+                0,
+                0..self.grass.instance_count(),
+            );
+        }
+        if let Some(gpu_profiler) = gpu_profiler {
+            gpu_profiler.end_inside_pass(render_pass, GpuPass::Grass);
+        }
     }
-}
\ No newline at end of file
+
+    fn run_tonemap_pass(&self, encoder: &mut wgpu::CommandEncoder, view: &wgpu::TextureView) {
+        let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("Tonemap Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                    store: wgpu::StoreOp::Store,
+                },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+
+        render_pass.set_pipeline(&self.tonemap.pipeline);
+        render_pass.set_bind_group(0, &self.tonemap.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
@@ -1,23 +1,42 @@
 use crate::grass::instance::GrassInstance;
+use super::depth::{DEPTH_COMPARE, DEPTH_FORMAT};
+use super::hdr::VELOCITY_FORMAT;
+use super::shadow::SHADOW_FORMAT;
 
 pub struct Pipeline {
     pub render_pipeline: wgpu::RenderPipeline,
+    /// Debug view counting the blade fragments per pixel
+    pub overdraw_pipeline: wgpu::RenderPipeline,
+    /// Draws the triangle edges, `None` without `POLYGON_MODE_LINE`
+    pub wireframe_pipeline: Option<wgpu::RenderPipeline>,
+    pub shadow_pipeline: wgpu::RenderPipeline,
 }
 
 impl Pipeline {
     pub fn new(
         device: &wgpu::Device,
         format: wgpu::TextureFormat,
+        sample_count: u32,
         camera_bind_group_layout: &wgpu::BindGroupLayout,
+        shadow_bind_group_layout: &wgpu::BindGroupLayout,
     ) -> Self {
         let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
             label: Some("Grass Shader"),
-            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/grass.wgsl").into()),
+            source: wgpu::ShaderSource::Wgsl(
+                concat!(
+                    include_str!("../shaders/shadow.wgsl"),
+                    include_str!("../shaders/lighting.wgsl"),
+                    include_str!("../shaders/temporal.wgsl"),
+                    include_str!("../shaders/debug.wgsl"),
+                    include_str!("../shaders/grass.wgsl"),
+                )
+                .into(),
+            ),
         });
 
         let render_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
             label: Some("Render Pipeline Layout"),
-            bind_group_layouts: &[camera_bind_group_layout],
+            bind_group_layouts: &[camera_bind_group_layout, shadow_bind_group_layout],
             push_constant_ranges: &[],
         });
 
@@ -31,50 +50,150 @@ impl Pipeline {
             }],
         };
 
-        let render_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
-            label: Some("Grass Render Pipeline"),
-            layout: Some(&render_pipeline_layout),
-            vertex: wgpu::VertexState {
-                module: &shader,
-                entry_point: Some("vs_main"),
-                buffers: &[vertex_buffer_layout, GrassInstance::vertex_buffer_layout()],
-                compilation_options: Default::default(),
+        let primitive = wgpu::PrimitiveState {
+            topology: wgpu::PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: wgpu::FrontFace::Ccw,
+            cull_mode: None,
+            polygon_mode: wgpu::PolygonMode::Fill,
+            unclipped_depth: false,
+            conservative: false,
+        };
+
+        let create_render_pipeline = |label, primitive, blend, depth_write_enabled, depth_compare, velocity_writes| {
+            device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: Some(label),
+                layout: Some(&render_pipeline_layout),
+                vertex: wgpu::VertexState {
+                    module: &shader,
+                    entry_point: Some("vs_main"),
+                    buffers: &[
+                        vertex_buffer_layout.clone(),
+                        GrassInstance::vertex_buffer_layout(),
+                        GrassInstance::previous_vertex_buffer_layout(),
+                    ],
+                    compilation_options: Default::default(),
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &shader,
+                    entry_point: Some("fs_main"),
+                    targets: &[
+                        Some(wgpu::ColorTargetState {
+                            format,
+                            blend: Some(blend),
+                            write_mask: wgpu::ColorWrites::ALL,
+                        }),
+                        Some(wgpu::ColorTargetState {
+                            format: VELOCITY_FORMAT,
+                            blend: None,
+                            write_mask: velocity_writes,
+                        }),
+                    ],
+                    compilation_options: Default::default(),
+                }),
+                primitive,
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: DEPTH_FORMAT,
+                    depth_write_enabled,
+                    depth_compare,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                // the fragment alpha fades out the blade edges, with MSAA it becomes coverage
+                multisample: wgpu::MultisampleState {
+                    count: sample_count,
+                    mask: !0,
+                    alpha_to_coverage_enabled: sample_count > 1,
+                },
+                multiview: None,
+                cache: None,
+            })
+        };
+
+        let render_pipeline = create_render_pipeline(
+            "Grass Render Pipeline",
+            primitive,
+            wgpu::BlendState::REPLACE,
+            true,
+            DEPTH_COMPARE,
+            wgpu::ColorWrites::ALL,
+        );
+        // every blade fragment adds to the colour, hidden or not
+        let overdraw_pipeline = create_render_pipeline(
+            "Grass Overdraw Pipeline",
+            primitive,
+            wgpu::BlendState {
+                color: wgpu::BlendComponent {
+                    src_factor: wgpu::BlendFactor::One,
+                    dst_factor: wgpu::BlendFactor::One,
+                    operation: wgpu::BlendOperation::Add,
+                },
+                alpha: wgpu::BlendComponent::REPLACE,
             },
-            fragment: Some(wgpu::FragmentState {
+            false,
+            wgpu::CompareFunction::Always,
+            wgpu::ColorWrites::empty(),
+        );
+        let wireframe_pipeline = device
+            .features()
+            .contains(wgpu::Features::POLYGON_MODE_LINE)
+            .then(|| {
+                create_render_pipeline(
+                    "Grass Wireframe Pipeline",
+                    wgpu::PrimitiveState {
+                        polygon_mode: wgpu::PolygonMode::Line,
+                        ..primitive
+                    },
+                    wgpu::BlendState::REPLACE,
+                    true,
+                    DEPTH_COMPARE,
+                    wgpu::ColorWrites::ALL,
+                )
+            });
+
+        let shadow_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Grass Shadow Pipeline Layout"),
+            bind_group_layouts: &[camera_bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        // depth only, the cascade matrix takes the place of the camera
+        let shadow_pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Grass Shadow Pipeline"),
+            layout: Some(&shadow_pipeline_layout),
+            vertex: wgpu::VertexState {
                 module: &shader,
-                entry_point: Some("fs_main"),
-                targets: &[Some(wgpu::ColorTargetState {
-                    format,
-                    blend: Some(wgpu::BlendState::REPLACE),
-                    write_mask: wgpu::ColorWrites::ALL,
-                })],
+                entry_point: Some("vs_shadow"),
+                buffers: &[
+                    vertex_buffer_layout,
+                    GrassInstance::vertex_buffer_layout(),
+                    GrassInstance::previous_vertex_buffer_layout(),
+                ],
                 compilation_options: Default::default(),
-            }),
-            primitive: wgpu::PrimitiveState {
-                topology: wgpu::PrimitiveTopology::TriangleList,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: None,
-                polygon_mode: wgpu::PolygonMode::Fill,
-                unclipped_depth: false,
-                conservative: false,
             },
+            fragment: None,
+            primitive,
             depth_stencil: Some(wgpu::DepthStencilState {
-                format: wgpu::TextureFormat::Depth32Float,
+                format: SHADOW_FORMAT,
                 depth_write_enabled: true,
                 depth_compare: wgpu::CompareFunction::Less,
                 stencil: wgpu::StencilState::default(),
-                bias: wgpu::DepthBiasState::default(),
+                bias: wgpu::DepthBiasState {
+                    constant: 2,
+                    slope_scale: 2.0,
+                    clamp: 0.0,
+                },
             }),
-            multisample: wgpu::MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
+            multisample: wgpu::MultisampleState::default(),
             multiview: None,
             cache: None,
         });
 
-        Self { render_pipeline }
+        Self {
+            render_pipeline,
+            overdraw_pipeline,
+            wireframe_pipeline,
+            shadow_pipeline,
+        }
     }
-}
\ No newline at end of file
+}
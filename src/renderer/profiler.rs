@@ -0,0 +1,275 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use super::stats::RollingAverage;
+
+/// Readback buffers in flight, frames whose buffers are all busy are not measured
+const READBACK_BUFFER_COUNT: usize = 3;
+/// How often the averages are written to the log
+const LOG_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Parts of the frame timed on the GPU
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GpuPass {
+    Compute,
+    Shadows,
+    /// The whole main render pass, sky included
+    Scene,
+    Ground,
+    Grass,
+}
+
+impl GpuPass {
+    pub const ALL: [GpuPass; 5] = [
+        GpuPass::Compute,
+        GpuPass::Shadows,
+        GpuPass::Scene,
+        GpuPass::Ground,
+        GpuPass::Grass,
+    ];
+
+    /// Queries written at the start and end of the pass
+    fn queries(self) -> (u32, u32) {
+        match self {
+            GpuPass::Compute => (0, 1),
+            GpuPass::Shadows => (2, 3),
+            GpuPass::Scene => (4, 5),
+            // written inside the scene pass
+            GpuPass::Ground => (6, 7),
+            GpuPass::Grass => (8, 9),
+        }
+    }
+
+    /// Ground and grass share the scene pass and need timestamps inside it
+    fn inside_pass(self) -> bool {
+        matches!(self, GpuPass::Ground | GpuPass::Grass)
+    }
+}
+
+/// Milliseconds of every pass in one frame, None where the pass was not measured
+pub type GpuFrameTimes = [Option<f32>; GpuPass::ALL.len()];
+
+const QUERY_COUNT: u32 = 10;
+const TIMESTAMP_SIZE: wgpu::BufferAddress = std::mem::size_of::<u64>() as wgpu::BufferAddress;
+const QUERY_BUFFER_SIZE: wgpu::BufferAddress = QUERY_COUNT as wgpu::BufferAddress * TIMESTAMP_SIZE;
+/// Every pass is resolved on its own, resolve offsets have to be aligned
+const RESOLVE_STRIDE: wgpu::BufferAddress = wgpu::QUERY_RESOLVE_BUFFER_ALIGNMENT;
+
+struct Readback {
+    buffer: wgpu::Buffer,
+    /// Set by the map callback once the results can be read
+    mapped: Arc<AtomicBool>,
+    in_flight: bool,
+    /// Passes left out of the frame in this buffer, their timestamps were not copied
+    skipped: [bool; GpuPass::ALL.len()],
+}
+
+/// Timestamp queries around the main passes, read back without stalling the frame
+pub struct GpuProfiler {
+    query_set: wgpu::QuerySet,
+    resolve_buffer: wgpu::Buffer,
+    readbacks: Vec<Readback>,
+    /// Readback filled by this frame's commands, mapped after submitting them
+    pending: Option<usize>,
+    /// Passes not recorded this frame
+    skipped: [bool; GpuPass::ALL.len()],
+    /// Nanoseconds per timestamp tick
+    period: f32,
+    inside_passes: bool,
+    averages: [RollingAverage; GpuPass::ALL.len()],
+    /// Every measured frame since recording started, for benchmarks
+    recorded: Option<Vec<GpuFrameTimes>>,
+    last_log: Instant,
+}
+
+impl GpuProfiler {
+    /// None when the device was created without `TIMESTAMP_QUERY`
+    pub fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Option<Self> {
+        if !device.features().contains(wgpu::Features::TIMESTAMP_QUERY) {
+            return None;
+        }
+        let query_set = device.create_query_set(&wgpu::QuerySetDescriptor {
+            label: Some("Timestamp Query Set"),
+            ty: wgpu::QueryType::Timestamp,
+            count: QUERY_COUNT,
+        });
+        let resolve_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Timestamp Resolve Buffer"),
+            size: GpuPass::ALL.len() as wgpu::BufferAddress * RESOLVE_STRIDE,
+            usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+            mapped_at_creation: false,
+        });
+        let readbacks = (0..READBACK_BUFFER_COUNT)
+            .map(|_| Readback {
+                buffer: device.create_buffer(&wgpu::BufferDescriptor {
+                    label: Some("Timestamp Readback Buffer"),
+                    size: QUERY_BUFFER_SIZE,
+                    usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                    mapped_at_creation: false,
+                }),
+                mapped: Arc::new(AtomicBool::new(false)),
+                in_flight: false,
+                skipped: Default::default(),
+            })
+            .collect();
+
+        Some(Self {
+            query_set,
+            resolve_buffer,
+            readbacks,
+            pending: None,
+            skipped: Default::default(),
+            period: queue.get_timestamp_period(),
+            inside_passes: device.features().contains(wgpu::Features::TIMESTAMP_QUERY_INSIDE_PASSES),
+            averages: Default::default(),
+            recorded: None,
+            last_log: Instant::now(),
+        })
+    }
+
+    pub fn compute_pass_writes(&self, pass: GpuPass) -> wgpu::ComputePassTimestampWrites<'_> {
+        let (begin, end) = pass.queries();
+        wgpu::ComputePassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: Some(begin),
+            end_of_pass_write_index: Some(end),
+        }
+    }
+
+    /// `first` and `last` select which ends of `pass` this render pass records,
+    /// so a pass split over several render passes is timed from start to finish
+    pub fn render_pass_writes(&self, pass: GpuPass, first: bool, last: bool) -> Option<wgpu::RenderPassTimestampWrites<'_>> {
+        let (begin, end) = pass.queries();
+        (first || last).then_some(wgpu::RenderPassTimestampWrites {
+            query_set: &self.query_set,
+            beginning_of_pass_write_index: first.then_some(begin),
+            end_of_pass_write_index: last.then_some(end),
+        })
+    }
+
+    /// Marks the start of `pass` inside the scene pass, ignored without `TIMESTAMP_QUERY_INSIDE_PASSES`
+    pub fn begin_inside_pass(&self, render_pass: &mut wgpu::RenderPass, pass: GpuPass) {
+        if self.inside_passes {
+            render_pass.write_timestamp(&self.query_set, pass.queries().0);
+        }
+    }
+
+    pub fn end_inside_pass(&self, render_pass: &mut wgpu::RenderPass, pass: GpuPass) {
+        if self.inside_passes {
+            render_pass.write_timestamp(&self.query_set, pass.queries().1);
+        }
+    }
+
+    /// Leaves `pass` out of this frame's measurements, for passes that were not recorded
+    pub fn skip_pass(&mut self, pass: GpuPass) {
+        if let Some(index) = GpuPass::ALL.iter().position(|&p| p == pass) {
+            self.skipped[index] = true;
+        }
+    }
+
+    /// Copies this frame's timestamps into a free readback buffer, call after the timed passes
+    ///
+    /// Only queries written this frame are resolved, resolving unwritten ones is undefined
+    /// and may stall the queue on some backends.
+    pub fn resolve(&mut self, encoder: &mut wgpu::CommandEncoder) {
+        let mut skipped = std::mem::take(&mut self.skipped);
+        for (pass, skipped) in GpuPass::ALL.iter().zip(&mut skipped) {
+            *skipped |= pass.inside_pass() && !self.inside_passes;
+        }
+        let Some(index) = self.readbacks.iter().position(|readback| !readback.in_flight) else {
+            return;
+        };
+        let readback = &mut self.readbacks[index];
+        readback.skipped = skipped;
+        for (slot, pass) in GpuPass::ALL.iter().enumerate() {
+            if skipped[slot] {
+                continue;
+            }
+            let (begin, end) = pass.queries();
+            let offset = slot as wgpu::BufferAddress * RESOLVE_STRIDE;
+            encoder.resolve_query_set(&self.query_set, begin..end + 1, &self.resolve_buffer, offset);
+            encoder.copy_buffer_to_buffer(
+                &self.resolve_buffer,
+                offset,
+                &readback.buffer,
+                begin as wgpu::BufferAddress * TIMESTAMP_SIZE,
+                (end - begin + 1) as wgpu::BufferAddress * TIMESTAMP_SIZE,
+            );
+        }
+        self.pending = Some(index);
+    }
+
+    /// Starts mapping the buffer filled by `resolve`, call after submitting the frame
+    pub fn after_submit(&mut self) {
+        let Some(index) = self.pending.take() else { return };
+        let readback = &mut self.readbacks[index];
+        readback.in_flight = true;
+        let mapped = readback.mapped.clone();
+        readback.buffer.slice(..).map_async(wgpu::MapMode::Read, move |result| {
+            // a failed map leaves the buffer in flight, it is then simply no longer used
+            if result.is_ok() {
+                mapped.store(true, Ordering::Release);
+            }
+        });
+    }
+
+    /// Collects the results of earlier frames that have finished on the GPU
+    pub fn collect(&mut self, device: &wgpu::Device) {
+        let _ = device.poll(wgpu::PollType::Poll);
+        for readback in &mut self.readbacks {
+            if !readback.mapped.swap(false, Ordering::Acquire) {
+                continue;
+            }
+            {
+                let data = readback.buffer.slice(..).get_mapped_range();
+                let timestamps: &[u64] = bytemuck::cast_slice(&data);
+                let mut frame = GpuFrameTimes::default();
+                let passes = GpuPass::ALL.iter().zip(&readback.skipped).zip(&mut self.averages).zip(&mut frame);
+                for (((pass, &skipped), average), time) in passes {
+                    if skipped {
+                        continue;
+                    }
+                    let (begin, end) = pass.queries();
+                    let ticks = timestamps[end as usize].wrapping_sub(timestamps[begin as usize]);
+                    let milliseconds = ticks as f32 * self.period / 1_000_000.0;
+                    average.push(milliseconds);
+                    *time = Some(milliseconds);
+                }
+                if let Some(recorded) = &mut self.recorded {
+                    recorded.push(frame);
+                }
+            }
+            readback.buffer.unmap();
+            readback.in_flight = false;
+        }
+
+        if self.last_log.elapsed() >= LOG_INTERVAL {
+            self.last_log = Instant::now();
+            let summary = GpuPass::ALL
+                .iter()
+                .filter_map(|&pass| Some(format!("{:?} {:.2} ms", pass, self.average_milliseconds(pass)?)))
+                .collect::<Vec<_>>();
+            if !summary.is_empty() {
+                log::info!("GPU time: {}", summary.join(", "));
+            }
+        }
+    }
+
+    /// Starts keeping the times of every measured frame, discarding earlier recordings
+    pub fn start_recording(&mut self) {
+        self.recorded = Some(Vec::new());
+    }
+
+    /// Stops recording and returns the frames measured since `start_recording`
+    pub fn stop_recording(&mut self) -> Vec<GpuFrameTimes> {
+        self.recorded.take().unwrap_or_default()
+    }
+
+    /// Rolling average of `pass` in milliseconds, None until it has been measured
+    pub fn average_milliseconds(&self, pass: GpuPass) -> Option<f32> {
+        let index = GpuPass::ALL.iter().position(|&p| p == pass)?;
+        let average = &self.averages[index];
+        (!average.is_empty()).then(|| average.average())
+    }
+}
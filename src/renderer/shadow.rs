@@ -0,0 +1,304 @@
+use glam::{Mat4, Vec3, Vec4};
+use wgpu::util::DeviceExt;
+use crate::camera::Camera;
+use crate::config::{
+    SHADOW_CASCADE_COUNT, SHADOW_DISTANCE, SHADOW_MAP_RESOLUTION, SHADOW_SPLIT_LAMBDA,
+};
+
+/// Size of the cascade arrays in shadow.wgsl
+pub const MAX_SHADOW_CASCADES: usize = 4;
+
+pub const SHADOW_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
+
+/// How far behind a cascade (towards the sun) casters are still captured
+const CASTER_MARGIN: f32 = 20.0;
+
+#[derive(Copy, Clone, Debug)]
+pub struct ShadowSettings {
+    /// Number of cascades, clamped to `MAX_SHADOW_CASCADES`
+    pub cascade_count: u32,
+    /// Width and height of each cascade in texels, clamped to the device limit
+    pub resolution: u32,
+    /// View distance covered by the last cascade
+    pub max_distance: f32,
+    pub split_lambda: f32,
+}
+
+impl Default for ShadowSettings {
+    fn default() -> Self {
+        Self {
+            cascade_count: SHADOW_CASCADE_COUNT,
+            resolution: SHADOW_MAP_RESOLUTION,
+            max_distance: SHADOW_DISTANCE,
+            split_lambda: SHADOW_SPLIT_LAMBDA,
+        }
+    }
+}
+
+/// Matches `ShadowUniforms` in shadow.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct ShadowUniforms {
+    light_view_proj: [[f32; 16]; MAX_SHADOW_CASCADES],
+    split_distances: [f32; MAX_SHADOW_CASCADES],
+    camera_forward: [f32; 4],
+    cascade_count: u32,
+    texel_size: f32,
+    _padding: [f32; 2],
+}
+
+/// Cascaded shadow map for the sun, rendered from the grass and ground geometry
+pub struct ShadowMap {
+    pub settings: ShadowSettings,
+    pub _texture: wgpu::Texture,
+    /// One depth view per cascade, used as render targets
+    pub cascade_views: Vec<wgpu::TextureView>,
+    cascade_buffers: Vec<wgpu::Buffer>,
+    /// Replaces the render bind group while drawing into a cascade
+    pub cascade_bind_groups: Vec<wgpu::BindGroup>,
+    uniform_buffer: wgpu::Buffer,
+    /// Group 1 of the grass and ground pipelines
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl ShadowMap {
+    pub fn new(
+        device: &wgpu::Device,
+        settings: ShadowSettings,
+        layout: &wgpu::BindGroupLayout,
+        render_bind_group_layout: &wgpu::BindGroupLayout,
+        shared_entries: &[wgpu::BindGroupEntry],
+    ) -> Self {
+        let settings = ShadowSettings {
+            cascade_count: settings.cascade_count.clamp(1, MAX_SHADOW_CASCADES as u32),
+            resolution: settings.resolution.clamp(1, device.limits().max_texture_dimension_2d),
+            ..settings
+        };
+
+        let texture = device.create_texture(&wgpu::TextureDescriptor {
+            label: Some("Shadow Map Texture"),
+            size: wgpu::Extent3d {
+                width: settings.resolution,
+                height: settings.resolution,
+                depth_or_array_layers: settings.cascade_count,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: SHADOW_FORMAT,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+            view_formats: &[],
+        });
+
+        let cascade_views = (0..settings.cascade_count)
+            .map(|layer| {
+                texture.create_view(&wgpu::TextureViewDescriptor {
+                    label: Some("Shadow Cascade View"),
+                    dimension: Some(wgpu::TextureViewDimension::D2),
+                    base_array_layer: layer,
+                    array_layer_count: Some(1),
+                    ..Default::default()
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let cascade_buffers = (0..settings.cascade_count)
+            .map(|_| {
+                device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                    label: Some("Shadow Cascade Buffer"),
+                    contents: bytemuck::cast_slice(Mat4::IDENTITY.as_ref()),
+                    usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        // same layout as the main render bind group with the light matrix as view_proj,
+        // `shared_entries` holds every other binding of that group
+        let cascade_bind_groups = cascade_buffers
+            .iter()
+            .map(|buffer| {
+                let mut entries = vec![wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: buffer.as_entire_binding(),
+                }];
+                entries.extend_from_slice(shared_entries);
+
+                device.create_bind_group(&wgpu::BindGroupDescriptor {
+                    label: Some("Shadow Cascade Bind Group"),
+                    layout: render_bind_group_layout,
+                    entries: &entries,
+                })
+            })
+            .collect::<Vec<_>>();
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Shadow Uniform Buffer"),
+            size: std::mem::size_of::<ShadowUniforms>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let view = texture.create_view(&wgpu::TextureViewDescriptor {
+            label: Some("Shadow Map View"),
+            dimension: Some(wgpu::TextureViewDimension::D2Array),
+            ..Default::default()
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("Shadow Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            compare: Some(wgpu::CompareFunction::LessEqual),
+            ..Default::default()
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Shadow Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: wgpu::BindingResource::TextureView(&view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: wgpu::BindingResource::Sampler(&sampler),
+                },
+            ],
+        });
+
+        Self {
+            settings,
+            _texture: texture,
+            cascade_views,
+            cascade_buffers,
+            cascade_bind_groups,
+            uniform_buffer,
+            bind_group,
+        }
+    }
+
+    pub fn create_bind_group_layout(device: &wgpu::Device) -> wgpu::BindGroupLayout {
+        device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Shadow Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Depth,
+                        view_dimension: wgpu::TextureViewDimension::D2Array,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 2,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Comparison),
+                    count: None,
+                },
+            ],
+        })
+    }
+
+    /// Fits every cascade around its slice of the camera frustum and uploads the matrices
+    pub fn update(&self, queue: &wgpu::Queue, camera: &Camera, light_dir: Vec3) {
+        let light_dir = light_dir.normalize();
+        let forward = (camera.target - camera.position).normalize();
+        let splits = self.split_distances(camera.znear);
+
+        let mut uniforms = ShadowUniforms {
+            light_view_proj: [[0.0; 16]; MAX_SHADOW_CASCADES],
+            split_distances: [0.0; MAX_SHADOW_CASCADES],
+            camera_forward: Vec4::from((forward, 0.0)).to_array(),
+            cascade_count: self.settings.cascade_count,
+            texel_size: 1.0 / self.settings.resolution as f32,
+            _padding: [0.0; 2],
+        };
+
+        let mut near = camera.znear;
+        for (cascade, &far) in splits.iter().enumerate() {
+            let light_view_proj = self.fit_cascade(camera, forward, light_dir, near, far);
+            queue.write_buffer(
+                &self.cascade_buffers[cascade],
+                0,
+                bytemuck::cast_slice(light_view_proj.as_ref()),
+            );
+            uniforms.light_view_proj[cascade] = light_view_proj.to_cols_array();
+            uniforms.split_distances[cascade] = far;
+            near = far;
+        }
+
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&uniforms));
+    }
+
+    /// Far distance of each cascade, blending uniform and logarithmic splits
+    fn split_distances(&self, near: f32) -> Vec<f32> {
+        let far = self.settings.max_distance;
+        let count = self.settings.cascade_count;
+        (1..=count)
+            .map(|i| {
+                let t = i as f32 / count as f32;
+                let log = near * (far / near).powf(t);
+                let linear = near + (far - near) * t;
+                self.settings.split_lambda * log + (1.0 - self.settings.split_lambda) * linear
+            })
+            .collect()
+    }
+
+    fn fit_cascade(&self, camera: &Camera, forward: Vec3, light_dir: Vec3, near: f32, far: f32) -> Mat4 {
+        // bounding sphere of the frustum slice, its size does not change when the
+        // camera rotates which keeps the shadow edges from swimming
+        let tan_half_fov = (camera.fovy * 0.5).tan();
+        let far_half_height = far * tan_half_fov;
+        let far_half_width = far_half_height * camera.aspect;
+        let center_distance = (near + far) * 0.5;
+        let far_corner = Vec3::new(far_half_width, far_half_height, far - center_distance);
+        let near_corner = Vec3::new(
+            near * tan_half_fov * camera.aspect,
+            near * tan_half_fov,
+            near - center_distance,
+        );
+        let radius = far_corner.length().max(near_corner.length());
+        let center = camera.position + forward * center_distance;
+
+        let up = if light_dir.y.abs() > 0.99 { Vec3::Z } else { Vec3::Y };
+
+        // snap the centre to whole texels in light space
+        let texel_size = 2.0 * radius / self.settings.resolution as f32;
+        let light_rotation = Mat4::look_at_rh(Vec3::ZERO, -light_dir, up);
+        let mut light_space_center = light_rotation.transform_point3(center);
+        light_space_center.x = (light_space_center.x / texel_size).floor() * texel_size;
+        light_space_center.y = (light_space_center.y / texel_size).floor() * texel_size;
+        let center = light_rotation.inverse().transform_point3(light_space_center);
+
+        let eye = center + light_dir * (radius + CASTER_MARGIN);
+        let view = Mat4::look_at_rh(eye, center, up);
+        let proj = Mat4::orthographic_rh(
+            -radius,
+            radius,
+            -radius,
+            radius,
+            0.0,
+            2.0 * radius + CASTER_MARGIN,
+        );
+        proj * view
+    }
+}
@@ -0,0 +1,212 @@
+use crate::camera::{Camera, Projection};
+use crate::lighting::Lighting;
+use glam::Mat4;
+use super::depth::DEPTH_FORMAT;
+use super::hdr::VELOCITY_FORMAT;
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum SkyModel {
+    /// Analytic atmosphere lit by the sun
+    #[default]
+    Atmosphere,
+    /// Blend from the horizon to the zenith colour of the current lighting
+    Gradient,
+}
+
+impl SkyModel {
+    fn as_gpu(self) -> u32 {
+        match self {
+            SkyModel::Atmosphere => 0,
+            SkyModel::Gradient => 1,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct SkySettings {
+    pub model: SkyModel,
+    /// Haze in the atmosphere, 2 is a clear day and 10 a hazy one
+    pub turbidity: f32,
+    /// Strength of the blue Rayleigh scattering
+    pub rayleigh: f32,
+    /// Strength of the Mie scattering around the sun
+    pub mie_coefficient: f32,
+    /// How concentrated the Mie glow is around the sun
+    pub mie_directional_g: f32,
+    /// Scales the atmosphere radiance to the brightness of the lit scene
+    pub exposure: f32,
+    /// Exposure of the atmosphere compressed into display range for the clamp tonemapper
+    pub clamped_exposure: f32,
+}
+
+impl Default for SkySettings {
+    fn default() -> Self {
+        Self {
+            model: SkyModel::default(),
+            turbidity: 2.0,
+            rayleigh: 2.0,
+            mie_coefficient: 0.005,
+            mie_directional_g: 0.8,
+            exposure: 0.25,
+            clamped_exposure: 0.15,
+        }
+    }
+}
+
+/// Matches `SkyUniforms` in sky.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct SkyUniforms {
+    inverse_view_proj: [f32; 16],
+    view_proj: [f32; 16],
+    previous_view_proj: [f32; 16],
+    camera_position: [f32; 4],
+    sun_direction: [f32; 3],
+    model: u32,
+    horizon_color: [f32; 3],
+    turbidity: f32,
+    zenith_color: [f32; 3],
+    rayleigh: f32,
+    mie_coefficient: f32,
+    mie_directional_g: f32,
+    exposure: f32,
+    /// 1 for an orthographic camera
+    orthographic: u32,
+    /// Above zero the atmosphere is compressed into display range with this exposure
+    clamped_exposure: f32,
+    _padding: [f32; 3],
+}
+
+/// Full-screen sky pass drawn at the start of the main render pass
+pub struct Sky {
+    pub settings: SkySettings,
+    pub pipeline: wgpu::RenderPipeline,
+    uniform_buffer: wgpu::Buffer,
+    pub bind_group: wgpu::BindGroup,
+}
+
+impl Sky {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat, sample_count: u32) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Sky Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/sky.wgsl").into()),
+        });
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Sky Uniform Buffer"),
+            size: std::mem::size_of::<SkyUniforms>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Sky Bind Group Layout"),
+            entries: &[wgpu::BindGroupLayoutEntry {
+                binding: 0,
+                visibility: wgpu::ShaderStages::FRAGMENT,
+                ty: wgpu::BindingType::Buffer {
+                    ty: wgpu::BufferBindingType::Uniform,
+                    has_dynamic_offset: false,
+                    min_binding_size: None,
+                },
+                count: None,
+            }],
+        });
+
+        let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Sky Bind Group"),
+            layout: &bind_group_layout,
+            entries: &[wgpu::BindGroupEntry {
+                binding: 0,
+                resource: uniform_buffer.as_entire_binding(),
+            }],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Sky Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Sky Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[
+                    Some(wgpu::ColorTargetState {
+                        format,
+                        blend: Some(wgpu::BlendState::REPLACE),
+                        write_mask: wgpu::ColorWrites::ALL,
+                    }),
+                    Some(wgpu::ColorTargetState {
+                        format: VELOCITY_FORMAT,
+                        blend: None,
+                        write_mask: wgpu::ColorWrites::ALL,
+                    }),
+                ],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            // drawn first and behind everything, the depth buffer is left untouched
+            depth_stencil: Some(wgpu::DepthStencilState {
+                format: DEPTH_FORMAT,
+                depth_write_enabled: false,
+                depth_compare: wgpu::CompareFunction::Always,
+                stencil: wgpu::StencilState::default(),
+                bias: wgpu::DepthBiasState::default(),
+            }),
+            multisample: wgpu::MultisampleState {
+                count: sample_count,
+                ..Default::default()
+            },
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            settings: SkySettings::default(),
+            pipeline,
+            uniform_buffer,
+            bind_group,
+        }
+    }
+
+    /// `previous_view_proj` is the unjittered camera matrix of the last frame, for motion vectors.
+    /// With `clamped` the scene is clipped rather than tonemapped, so the atmosphere compresses itself
+    pub fn update(
+        &self,
+        queue: &wgpu::Queue,
+        camera: &Camera,
+        lighting: &Lighting,
+        previous_view_proj: Mat4,
+        clamped: bool,
+    ) {
+        let uniforms = SkyUniforms {
+            inverse_view_proj: camera.build_view_projection_matrix().inverse().to_cols_array(),
+            view_proj: camera.build_unjittered_view_projection_matrix().to_cols_array(),
+            previous_view_proj: previous_view_proj.to_cols_array(),
+            camera_position: [camera.position.x, camera.position.y, camera.position.z, 0.0],
+            sun_direction: lighting.sky_sun_direction.to_array(),
+            model: self.settings.model.as_gpu(),
+            horizon_color: lighting.horizon_color.to_array(),
+            turbidity: self.settings.turbidity,
+            zenith_color: lighting.zenith_color.to_array(),
+            rayleigh: self.settings.rayleigh,
+            mie_coefficient: self.settings.mie_coefficient,
+            mie_directional_g: self.settings.mie_directional_g,
+            exposure: self.settings.exposure,
+            orthographic: matches!(camera.projection, Projection::Orthographic { .. }) as u32,
+            clamped_exposure: if clamped { self.settings.clamped_exposure } else { 0.0 },
+            _padding: [0.0; 3],
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&uniforms));
+    }
+}
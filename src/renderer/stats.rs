@@ -0,0 +1,79 @@
+use std::collections::VecDeque;
+use std::time::Instant;
+
+/// Frames the rolling averages are taken over
+const FRAME_STATS_WINDOW: usize = 120;
+
+/// Mean and maximum of the last `FRAME_STATS_WINDOW` samples
+#[derive(Clone, Debug)]
+pub struct RollingAverage {
+    samples: VecDeque<f32>,
+}
+
+impl Default for RollingAverage {
+    fn default() -> Self {
+        Self {
+            samples: VecDeque::with_capacity(FRAME_STATS_WINDOW),
+        }
+    }
+}
+
+impl RollingAverage {
+    pub fn push(&mut self, sample: f32) {
+        if self.samples.len() == FRAME_STATS_WINDOW {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    pub fn average(&self) -> f32 {
+        if self.samples.is_empty() {
+            return 0.0;
+        }
+        self.samples.iter().sum::<f32>() / self.samples.len() as f32
+    }
+
+    pub fn max(&self) -> f32 {
+        self.samples.iter().copied().fold(0.0, f32::max)
+    }
+}
+
+/// Wall-clock frame times of the last frames, independent of the simulation clock
+#[derive(Default)]
+pub struct FrameStats {
+    last_frame: Option<Instant>,
+    frame_times: RollingAverage,
+    /// Fixed simulation steps run in the last frame
+    pub simulation_steps: u32,
+}
+
+impl FrameStats {
+    /// Call once per frame
+    pub fn record_frame(&mut self, simulation_steps: u32) {
+        let now = Instant::now();
+        if let Some(last_frame) = self.last_frame {
+            self.frame_times.push((now - last_frame).as_secs_f32());
+        }
+        self.last_frame = Some(now);
+        self.simulation_steps = simulation_steps;
+    }
+
+    /// Average frame time in seconds
+    pub fn average_frame_time(&self) -> f32 {
+        self.frame_times.average()
+    }
+
+    /// Longest frame time in seconds
+    pub fn max_frame_time(&self) -> f32 {
+        self.frame_times.max()
+    }
+
+    pub fn frames_per_second(&self) -> f32 {
+        let average = self.average_frame_time();
+        if average > 0.0 { 1.0 / average } else { 0.0 }
+    }
+}
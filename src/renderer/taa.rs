@@ -0,0 +1,297 @@
+use glam::Vec2;
+use super::hdr::{HdrTarget, HDR_FORMAT};
+
+/// Number of sub-pixel offsets cycled through before the jitter repeats
+const JITTER_SEQUENCE_LENGTH: u32 = 8;
+
+#[derive(Copy, Clone, Debug)]
+pub struct TaaSettings {
+    pub enabled: bool,
+    /// Share of the reprojected history in the resolved colour
+    pub history_weight: f32,
+}
+
+impl Default for TaaSettings {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            history_weight: 0.9,
+        }
+    }
+}
+
+/// Matches `TaaUniforms` in taa.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct TaaUniforms {
+    history_weight: f32,
+    history_valid: u32,
+    _padding: [f32; 2],
+}
+
+/// Temporal anti-aliasing, accumulates the jittered HDR scene over frames
+pub struct Taa {
+    pub settings: TaaSettings,
+    pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    sampler: wgpu::Sampler,
+    uniform_buffer: wgpu::Buffer,
+    /// Ping-ponged, one is read as the history while the other is resolved into
+    history_textures: [wgpu::Texture; 2],
+    history_views: [wgpu::TextureView; 2],
+    /// `bind_groups[i]` reads `history_views[i]`
+    bind_groups: [wgpu::BindGroup; 2],
+    read_index: usize,
+    history_valid: bool,
+    frame: u32,
+}
+
+impl Taa {
+    pub fn new(device: &wgpu::Device, hdr: &HdrTarget, velocity: &HdrTarget) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("TAA Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/taa.wgsl").into()),
+        });
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("TAA Uniform Buffer"),
+            size: std::mem::size_of::<TaaUniforms>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+            label: Some("TAA History Sampler"),
+            address_mode_u: wgpu::AddressMode::ClampToEdge,
+            address_mode_v: wgpu::AddressMode::ClampToEdge,
+            mag_filter: wgpu::FilterMode::Linear,
+            min_filter: wgpu::FilterMode::Linear,
+            ..Default::default()
+        });
+
+        let texture_entry = |binding, filterable| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Texture {
+                sample_type: wgpu::TextureSampleType::Float { filterable },
+                view_dimension: wgpu::TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("TAA Bind Group Layout"),
+            entries: &[
+                texture_entry(0, false),
+                texture_entry(1, false),
+                texture_entry(2, true),
+                wgpu::BindGroupLayoutEntry {
+                    binding: 3,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+                    count: None,
+                },
+                wgpu::BindGroupLayoutEntry {
+                    binding: 4,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Buffer {
+                        ty: wgpu::BufferBindingType::Uniform,
+                        has_dynamic_offset: false,
+                        min_binding_size: None,
+                    },
+                    count: None,
+                },
+            ],
+        });
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("TAA Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("TAA Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format: HDR_FORMAT,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        let (history_textures, history_views, bind_groups) = Self::create_history(
+            device,
+            &bind_group_layout,
+            &sampler,
+            &uniform_buffer,
+            hdr,
+            velocity,
+        );
+
+        Self {
+            settings: TaaSettings::default(),
+            pipeline,
+            bind_group_layout,
+            sampler,
+            uniform_buffer,
+            history_textures,
+            history_views,
+            bind_groups,
+            read_index: 0,
+            history_valid: false,
+            frame: 0,
+        }
+    }
+
+    #[allow(clippy::type_complexity)]
+    fn create_history(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        sampler: &wgpu::Sampler,
+        uniform_buffer: &wgpu::Buffer,
+        hdr: &HdrTarget,
+        velocity: &HdrTarget,
+    ) -> ([wgpu::Texture; 2], [wgpu::TextureView; 2], [wgpu::BindGroup; 2]) {
+        let textures = [0, 1].map(|_| {
+            device.create_texture(&wgpu::TextureDescriptor {
+                label: Some("TAA History Texture"),
+                size: hdr.texture.size(),
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: HDR_FORMAT,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT
+                    | wgpu::TextureUsages::TEXTURE_BINDING
+                    | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            })
+        });
+        let views = [0, 1].map(|i| textures[i].create_view(&wgpu::TextureViewDescriptor::default()));
+        let bind_groups = [0, 1].map(|i| {
+            device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: Some("TAA Bind Group"),
+                layout,
+                entries: &[
+                    wgpu::BindGroupEntry {
+                        binding: 0,
+                        resource: wgpu::BindingResource::TextureView(&hdr.view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 1,
+                        resource: wgpu::BindingResource::TextureView(&velocity.view),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 2,
+                        resource: wgpu::BindingResource::TextureView(&views[i]),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 3,
+                        resource: wgpu::BindingResource::Sampler(sampler),
+                    },
+                    wgpu::BindGroupEntry {
+                        binding: 4,
+                        resource: uniform_buffer.as_entire_binding(),
+                    },
+                ],
+            })
+        });
+        (textures, views, bind_groups)
+    }
+
+    /// Recreates the history for new scene targets after a resize
+    pub fn set_targets(&mut self, device: &wgpu::Device, hdr: &HdrTarget, velocity: &HdrTarget) {
+        (self.history_textures, self.history_views, self.bind_groups) = Self::create_history(
+            device,
+            &self.bind_group_layout,
+            &self.sampler,
+            &self.uniform_buffer,
+            hdr,
+            velocity,
+        );
+        self.history_valid = false;
+    }
+
+    /// Drops the accumulated history, the next resolve starts from the current frame
+    pub fn invalidate_history(&mut self) {
+        self.history_valid = false;
+    }
+
+    /// Sub-pixel projection offset in NDC for the next frame, from a Halton(2, 3) sequence
+    pub fn next_jitter(&mut self, width: u32, height: u32) -> Vec2 {
+        self.frame = (self.frame + 1) % JITTER_SEQUENCE_LENGTH;
+        let offset = Vec2::new(halton(self.frame + 1, 2), halton(self.frame + 1, 3)) - 0.5;
+        offset * 2.0 / Vec2::new(width as f32, height as f32)
+    }
+
+    /// Resolves the scene against the history and writes the result back into `hdr`
+    pub fn run(&mut self, encoder: &mut wgpu::CommandEncoder, queue: &wgpu::Queue, hdr: &HdrTarget) {
+        let uniforms = TaaUniforms {
+            history_weight: self.settings.history_weight,
+            history_valid: self.history_valid as u32,
+            _padding: [0.0; 2],
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&uniforms));
+
+        let write_index = 1 - self.read_index;
+        {
+            let mut render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: Some("TAA Pass"),
+                color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                    view: &self.history_views[write_index],
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: wgpu::StoreOp::Store,
+                    },
+                    depth_slice: None,
+                })],
+                depth_stencil_attachment: None,
+                timestamp_writes: None,
+                occlusion_query_set: None,
+            });
+
+            render_pass.set_pipeline(&self.pipeline);
+            render_pass.set_bind_group(0, &self.bind_groups[self.read_index], &[]);
+            render_pass.draw(0..3, 0..1);
+        }
+
+        // the later passes keep reading the HDR target
+        encoder.copy_texture_to_texture(
+            self.history_textures[write_index].as_image_copy(),
+            hdr.texture.as_image_copy(),
+            hdr.texture.size(),
+        );
+
+        self.read_index = write_index;
+        self.history_valid = true;
+    }
+}
+
+/// Radical inverse of `index` in `base`
+fn halton(mut index: u32, base: u32) -> f32 {
+    let mut result = 0.0;
+    let mut fraction = 1.0;
+    while index > 0 {
+        fraction /= base as f32;
+        result += fraction * (index % base) as f32;
+        index /= base;
+    }
+    result
+}
@@ -0,0 +1,230 @@
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub enum Tonemapper {
+    /// Clips to display range like the renderer did before HDR, the sky compresses itself
+    #[default]
+    Clamp,
+    Aces,
+    AgX,
+    Reinhard,
+}
+
+impl Tonemapper {
+    fn as_gpu(self) -> u32 {
+        match self {
+            Tonemapper::Aces => 0,
+            Tonemapper::AgX => 1,
+            Tonemapper::Reinhard => 2,
+            Tonemapper::Clamp => 3,
+        }
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            Tonemapper::Clamp => Tonemapper::Aces,
+            Tonemapper::Aces => Tonemapper::AgX,
+            Tonemapper::AgX => Tonemapper::Reinhard,
+            Tonemapper::Reinhard => Tonemapper::Clamp,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct TonemapSettings {
+    pub tonemapper: Tonemapper,
+    /// Multiplies the scene before tonemapping, also applied on top of auto-exposure
+    pub exposure: f32,
+    /// Expose for the average scene luminance measured by `AutoExposure`
+    pub auto_exposure: bool,
+    /// Luminance the scene average is mapped to with auto-exposure
+    pub key_value: f32,
+}
+
+impl Default for TonemapSettings {
+    fn default() -> Self {
+        Self {
+            tonemapper: Tonemapper::default(),
+            exposure: 1.0,
+            auto_exposure: false,
+            key_value: 0.18,
+        }
+    }
+}
+
+/// Matches `TonemapUniforms` in tonemap.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+struct TonemapUniforms {
+    exposure: f32,
+    tonemapper: u32,
+    auto_exposure: u32,
+    key_value: f32,
+}
+
+/// Full-screen pass resolving the HDR target into the swapchain
+pub struct Tonemap {
+    pub settings: TonemapSettings,
+    pub pipeline: wgpu::RenderPipeline,
+    bind_group_layout: wgpu::BindGroupLayout,
+    pub bind_group: wgpu::BindGroup,
+    uniform_buffer: wgpu::Buffer,
+}
+
+impl Tonemap {
+    pub fn new(
+        device: &wgpu::Device,
+        format: wgpu::TextureFormat,
+        hdr_view: &wgpu::TextureView,
+        luminance_buffer: &wgpu::Buffer,
+    ) -> Self {
+        let shader = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+            label: Some("Tonemap Shader"),
+            source: wgpu::ShaderSource::Wgsl(include_str!("../shaders/tonemap.wgsl").into()),
+        });
+
+        let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("Tonemap Uniform Buffer"),
+            size: std::mem::size_of::<TonemapUniforms>() as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let uniform_entry = |binding| wgpu::BindGroupLayoutEntry {
+            binding,
+            visibility: wgpu::ShaderStages::FRAGMENT,
+            ty: wgpu::BindingType::Buffer {
+                ty: wgpu::BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        };
+        let bind_group_layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+            label: Some("Tonemap Bind Group Layout"),
+            entries: &[
+                wgpu::BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: wgpu::ShaderStages::FRAGMENT,
+                    ty: wgpu::BindingType::Texture {
+                        sample_type: wgpu::TextureSampleType::Float { filterable: false },
+                        view_dimension: wgpu::TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                uniform_entry(1),
+                uniform_entry(2),
+            ],
+        });
+
+        let bind_group = Self::create_bind_group(
+            device,
+            &bind_group_layout,
+            hdr_view,
+            &uniform_buffer,
+            luminance_buffer,
+        );
+
+        let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+            label: Some("Tonemap Pipeline Layout"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+
+        let pipeline = device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+            label: Some("Tonemap Pipeline"),
+            layout: Some(&pipeline_layout),
+            vertex: wgpu::VertexState {
+                module: &shader,
+                entry_point: Some("vs_main"),
+                buffers: &[],
+                compilation_options: Default::default(),
+            },
+            fragment: Some(wgpu::FragmentState {
+                module: &shader,
+                entry_point: Some("fs_main"),
+                targets: &[Some(wgpu::ColorTargetState {
+                    format,
+                    blend: Some(wgpu::BlendState::REPLACE),
+                    write_mask: wgpu::ColorWrites::ALL,
+                })],
+                compilation_options: Default::default(),
+            }),
+            primitive: wgpu::PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: wgpu::MultisampleState::default(),
+            multiview: None,
+            cache: None,
+        });
+
+        Self {
+            settings: TonemapSettings::default(),
+            pipeline,
+            bind_group_layout,
+            bind_group,
+            uniform_buffer,
+        }
+    }
+
+    fn create_bind_group(
+        device: &wgpu::Device,
+        layout: &wgpu::BindGroupLayout,
+        hdr_view: &wgpu::TextureView,
+        uniform_buffer: &wgpu::Buffer,
+        luminance_buffer: &wgpu::Buffer,
+    ) -> wgpu::BindGroup {
+        device.create_bind_group(&wgpu::BindGroupDescriptor {
+            label: Some("Tonemap Bind Group"),
+            layout,
+            entries: &[
+                wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: wgpu::BindingResource::TextureView(hdr_view),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 1,
+                    resource: uniform_buffer.as_entire_binding(),
+                },
+                wgpu::BindGroupEntry {
+                    binding: 2,
+                    resource: luminance_buffer.as_entire_binding(),
+                },
+            ],
+        })
+    }
+
+    /// Points the pass at a new HDR target after a resize
+    pub fn set_hdr_view(
+        &mut self,
+        device: &wgpu::Device,
+        hdr_view: &wgpu::TextureView,
+        luminance_buffer: &wgpu::Buffer,
+    ) {
+        self.bind_group = Self::create_bind_group(
+            device,
+            &self.bind_group_layout,
+            hdr_view,
+            &self.uniform_buffer,
+            luminance_buffer,
+        );
+    }
+
+    /// With `passthrough` the HDR target is copied unchanged, for debug views with fixed colours
+    pub fn update(&self, queue: &wgpu::Queue, passthrough: bool) {
+        let uniforms = if passthrough {
+            TonemapUniforms {
+                exposure: 1.0,
+                tonemapper: Tonemapper::Clamp.as_gpu(),
+                auto_exposure: 0,
+                key_value: self.settings.key_value,
+            }
+        } else {
+            TonemapUniforms {
+                exposure: self.settings.exposure,
+                tonemapper: self.settings.tonemapper.as_gpu(),
+                auto_exposure: self.settings.auto_exposure as u32,
+                key_value: self.settings.key_value,
+            }
+        };
+        queue.write_buffer(&self.uniform_buffer, 0, bytemuck::bytes_of(&uniforms));
+    }
+}
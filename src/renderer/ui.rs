@@ -0,0 +1,68 @@
+/// Tessellated egui output of one frame, drawn on top of the tonemapped image
+pub struct UiFrame {
+    pub primitives: Vec<egui::ClippedPrimitive>,
+    pub textures_delta: egui::TexturesDelta,
+    pub pixels_per_point: f32,
+}
+
+/// Draws egui into the swapchain image after tonemapping
+pub struct UiRenderer {
+    renderer: egui_wgpu::Renderer,
+    /// Drawn and cleared by the next `run`
+    frame: Option<UiFrame>,
+}
+
+impl UiRenderer {
+    pub fn new(device: &wgpu::Device, format: wgpu::TextureFormat) -> Self {
+        Self {
+            renderer: egui_wgpu::Renderer::new(device, format, egui_wgpu::RendererOptions::default()),
+            frame: None,
+        }
+    }
+
+    pub fn set_frame(&mut self, frame: UiFrame) {
+        self.frame = Some(frame);
+    }
+
+    pub fn run(
+        &mut self,
+        device: &wgpu::Device,
+        queue: &wgpu::Queue,
+        encoder: &mut wgpu::CommandEncoder,
+        view: &wgpu::TextureView,
+        size: [u32; 2],
+    ) {
+        let Some(frame) = self.frame.take() else { return };
+        let screen = egui_wgpu::ScreenDescriptor {
+            size_in_pixels: size,
+            pixels_per_point: frame.pixels_per_point,
+        };
+
+        for (id, delta) in &frame.textures_delta.set {
+            self.renderer.update_texture(device, queue, *id, delta);
+        }
+        // egui only returns extra command buffers for paint callbacks, which are not used
+        self.renderer.update_buffers(device, queue, encoder, &frame.primitives, &screen);
+
+        let render_pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+            label: Some("UI Pass"),
+            color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+                view,
+                resolve_target: None,
+                ops: wgpu::Operations {
+                    load: wgpu::LoadOp::Load,
+                    store: wgpu::StoreOp::Store,
+                },
+                depth_slice: None,
+            })],
+            depth_stencil_attachment: None,
+            timestamp_writes: None,
+            occlusion_query_set: None,
+        });
+        self.renderer.render(&mut render_pass.forget_lifetime(), &frame.primitives, &screen);
+
+        for id in &frame.textures_delta.free {
+            self.renderer.free_texture(id);
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use std::fmt::Display;
+use std::io;
+use std::path::Path;
+use std::str::FromStr;
+
+use glam::Vec3;
+
+use crate::config::SUN_DIRECTION;
+use crate::renderer::Renderer;
+use crate::wind::{WindLayer, MAX_WIND_LAYERS};
+
+/// Tunable parameters saved from the settings panel
+///
+/// Stored as text with one `key = value` per line, vectors as three numbers.
+/// Keys missing from a file keep their current value when it is applied.
+#[derive(Clone, Debug, Default)]
+pub struct Settings {
+    values: Vec<(String, String)>,
+}
+
+impl Settings {
+    pub fn load(path: &Path) -> io::Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        std::fs::write(path, self.to_text())
+    }
+
+    fn parse(text: &str) -> io::Result<Self> {
+        let mut values = Vec::new();
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line.split_once('=').ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("line {}: expected key = value", number + 1),
+                )
+            })?;
+            values.push((key.trim().to_string(), value.trim().to_string()));
+        }
+        Ok(Self { values })
+    }
+
+    fn to_text(&self) -> String {
+        let mut text = String::from("# procedural grass settings\n");
+        for (key, value) in &self.values {
+            text.push_str(&format!("{} = {}\n", key, value));
+        }
+        text
+    }
+
+    /// Current parameters of `renderer`
+    pub fn capture(renderer: &mut Renderer) -> Self {
+        let mut settings = Self::default();
+        exchange(&mut Exchange::Capture(&mut settings), renderer);
+        settings
+    }
+
+    /// Applies every stored value to `renderer`, unparsable values are skipped with a warning
+    pub fn apply(&self, renderer: &mut Renderer) {
+        exchange(&mut Exchange::Apply(self), renderer);
+        // a hand-edited direction may be unnormalised or zero
+        let lighting = renderer.lighting_mut();
+        lighting.sun_direction = lighting
+            .sun_direction
+            .normalize_or(Vec3::from(SUN_DIRECTION).normalize());
+        // the sky follows the sun unless the day cycle moves both
+        lighting.sky_sun_direction = lighting.sun_direction;
+    }
+
+    fn get(&self, key: &str) -> Option<&str> {
+        // later lines win, like assignments
+        self.values.iter().rev().find(|(k, _)| k == key).map(|(_, v)| v.as_str())
+    }
+}
+
+/// Direction values move between the renderer and a `Settings`
+enum Exchange<'a> {
+    Capture(&'a mut Settings),
+    Apply(&'a Settings),
+}
+
+impl Exchange<'_> {
+    fn value<T: FromStr + Display>(&mut self, key: &str, value: &mut T) {
+        match self {
+            Exchange::Capture(settings) => settings.values.push((key.to_string(), value.to_string())),
+            Exchange::Apply(settings) => {
+                let Some(text) = settings.get(key) else { return };
+                match text.parse() {
+                    Ok(parsed) => *value = parsed,
+                    Err(_) => log::warn!("Ignoring setting {} with invalid value {:?}", key, text),
+                }
+            }
+        }
+    }
+
+    fn vec3(&mut self, key: &str, value: &mut Vec3) {
+        match self {
+            Exchange::Capture(settings) => {
+                let text = format!("{} {} {}", value.x, value.y, value.z);
+                settings.values.push((key.to_string(), text));
+            }
+            Exchange::Apply(settings) => {
+                let Some(text) = settings.get(key) else { return };
+                let numbers = text
+                    .split_whitespace()
+                    .map(str::parse::<f32>)
+                    .collect::<Result<Vec<_>, _>>();
+                match numbers.as_deref() {
+                    Ok(&[x, y, z]) => *value = Vec3::new(x, y, z),
+                    _ => log::warn!("Ignoring setting {} with invalid value {:?}", key, text),
+                }
+            }
+        }
+    }
+}
+
+/// Lists every saved parameter once, for both directions
+fn exchange(exchange: &mut Exchange, renderer: &mut Renderer) {
+    let wind = renderer.wind_mut();
+    exchange.value("wind.direction", &mut wind.direction);
+    exchange.value("wind.strength", &mut wind.strength);
+    exchange.value("wind.gust_frequency", &mut wind.gust_frequency);
+    exchange.value("wind.gust_amplitude", &mut wind.gust_amplitude);
+    exchange.value("wind.turbulence_scale", &mut wind.turbulence_scale);
+    exchange.value("wind.turbulence_speed", &mut wind.turbulence_speed);
+    exchange.value("wind.turbulence_strength", &mut wind.turbulence_strength);
+    let mut layer_count = wind.layers.len();
+    exchange.value("wind.layer_count", &mut layer_count);
+    wind.layers.resize(layer_count.min(MAX_WIND_LAYERS), WindLayer::new(0.05, 1.0, 0.0));
+    for (i, layer) in wind.layers.iter_mut().enumerate() {
+        exchange.value(&format!("wind.layer{}.scale", i), &mut layer.scale);
+        exchange.value(&format!("wind.layer{}.speed", i), &mut layer.speed);
+        exchange.value(&format!("wind.layer{}.amplitude", i), &mut layer.amplitude);
+        exchange.value(&format!("wind.layer{}.angle_offset", i), &mut layer.angle_offset);
+    }
+
+    let blade = renderer.blade_settings_mut();
+    exchange.value("blade.height", &mut blade.height);
+    exchange.value("blade.width", &mut blade.width);
+    exchange.value("blade.bend", &mut blade.bend);
+    exchange.value("blade.stiffness", &mut blade.stiffness);
+    exchange.value("blade.damping", &mut blade.damping);
+    exchange.value("blade.gravity", &mut blade.gravity);
+
+    let material = renderer.grass_material_mut();
+    exchange.value("material.season", &mut material.season);
+    exchange.value("material.variation_strength", &mut material.variation_strength);
+    exchange.value("material.dry_patch_coverage", &mut material.dry_patch_coverage);
+
+    let lighting = renderer.lighting_mut();
+    exchange.vec3("lighting.sun_direction", &mut lighting.sun_direction);
+    exchange.vec3("lighting.sun_color", &mut lighting.sun_color);
+    exchange.value("lighting.sun_intensity", &mut lighting.sun_intensity);
+    exchange.value("lighting.ambient_intensity", &mut lighting.ambient_intensity);
+    exchange.value("lighting.fog_density", &mut lighting.fog.density);
+    exchange.value("lighting.fog_height_falloff", &mut lighting.fog.height_falloff);
+    exchange.value("lighting.translucency_strength", &mut lighting.translucency.strength);
+    if let Some(day_cycle) = renderer.day_cycle_mut() {
+        exchange.value("day_cycle.time_of_day", &mut day_cycle.time_of_day);
+        exchange.value("day_cycle.speed", &mut day_cycle.speed);
+    }
+
+    let tonemap = renderer.tonemap_settings_mut();
+    exchange.value("tonemap.exposure", &mut tonemap.exposure);
+    exchange.value("tonemap.auto_exposure", &mut tonemap.auto_exposure);
+
+    exchange.value("camera.fovy", &mut renderer.camera_mut().fovy);
+    let controller = renderer.camera_controller_mut();
+    exchange.value("camera.fly_speed", &mut controller.fly_speed);
+    exchange.value("camera.rotation_speed", &mut controller.rotation_speed);
+    exchange.value("camera.zoom_speed", &mut controller.zoom_speed);
+    exchange.value("camera.smoothing", &mut controller.smoothing);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn values_round_trip_through_text() {
+        let mut strength = 0.65_f32;
+        let mut enabled = true;
+        let mut direction = Vec3::new(0.25, -1.0, 3.5);
+        let mut captured = Settings::default();
+        let mut capture = Exchange::Capture(&mut captured);
+        capture.value("wind.strength", &mut strength);
+        capture.value("tonemap.auto_exposure", &mut enabled);
+        capture.vec3("lighting.sun_direction", &mut direction);
+
+        let parsed = Settings::parse(&captured.to_text()).unwrap();
+        let (mut strength, mut enabled, mut direction) = (0.0_f32, false, Vec3::ZERO);
+        let mut apply = Exchange::Apply(&parsed);
+        apply.value("wind.strength", &mut strength);
+        apply.value("tonemap.auto_exposure", &mut enabled);
+        apply.vec3("lighting.sun_direction", &mut direction);
+        assert_eq!(strength, 0.65);
+        assert!(enabled);
+        assert_eq!(direction, Vec3::new(0.25, -1.0, 3.5));
+    }
+
+    #[test]
+    fn parse_skips_comments_and_blank_lines() {
+        let settings = Settings::parse("# comment\n\n  wind.strength =  2 \n").unwrap();
+        assert_eq!(settings.get("wind.strength"), Some("2"));
+    }
+
+    #[test]
+    fn parse_rejects_line_without_equals() {
+        let error = Settings::parse("wind.strength = 1\nwind.direction 2\n").unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        assert!(error.to_string().contains("line 2"));
+    }
+
+    #[test]
+    fn invalid_values_keep_the_current_value() {
+        let settings = Settings::parse("wind.strength = strong\nlighting.sun_direction = 1 2\n").unwrap();
+        let (mut strength, mut direction) = (0.65_f32, Vec3::Y);
+        let mut apply = Exchange::Apply(&settings);
+        apply.value("wind.strength", &mut strength);
+        apply.vec3("lighting.sun_direction", &mut direction);
+        assert_eq!(strength, 0.65);
+        assert_eq!(direction, Vec3::Y);
+    }
+
+    #[test]
+    fn later_lines_win() {
+        let settings = Settings::parse("wind.strength = 1\nwind.strength = 2\n").unwrap();
+        assert_eq!(settings.get("wind.strength"), Some("2"));
+    }
+}
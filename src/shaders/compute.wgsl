@@ -7,6 +7,30 @@ struct GrassInstance {
     tilt: f32,
     facing: vec2<f32>,
     blade_hash: f32,
+    sway_angle: f32,
+}
+
+// Tip offset is the lean of the blade in radians, split into world x/z
+struct BladeState {
+    tip_offset: vec2<f32>,
+    velocity: vec2<f32>,
+}
+
+struct WindLayer {
+    scale: f32,
+    speed: f32,
+    amplitude: f32,
+    angle_offset: f32,
+}
+
+// kind: 0 = radial, 1 = directional cone, 2 = vortex
+struct WindEmitter {
+    position: vec2<f32>,
+    radius: f32,
+    strength: f32,
+    direction: vec2<f32>,
+    cone_cos: f32,
+    kind: u32,
 }
 
 struct WindUniforms {
@@ -15,14 +39,40 @@ struct WindUniforms {
     wind_angle: f32,
     instance_count: f32,
     blade_base_height: f32,
-    _padding1: f32,
-    _padding2: f32,
-    _padding3: f32,
+    delta_time: f32,
+    stiffness: f32,
+    damping: f32,
+    gravity: f32,
+    gust_frequency: f32,
+    gust_amplitude: f32,
+    turbulence_scale: f32,
+    turbulence_speed: f32,
+    turbulence_strength: f32,
+    layer_count: u32,
+    emitter_count: u32,
+    flow_map_scroll: vec2<f32>,
+    // 0 = disabled, 1 = override, 2 = bias
+    flow_map_mode: u32,
+    flow_map_strength: f32,
+    field_half_size: f32,
+    blade_width: f32,
+    blade_bend: f32,
+    _padding: f32,
+    layers: array<WindLayer, 4>,
 }
 
 @group(0) @binding(0) var<storage, read> input_positions: array<GrassInstance>;
 @group(0) @binding(1) var<storage, read_write> output_positions: array<GrassInstance>;
 @group(0) @binding(2) var<uniform> wind: WindUniforms;
+@group(0) @binding(3) var<storage, read> state_in: array<BladeState>;
+@group(0) @binding(4) var<storage, read_write> state_out: array<BladeState>;
+@group(0) @binding(5) var<storage, read> emitters: array<WindEmitter>;
+
+@group(1) @binding(0) var flow_map: texture_2d<f32>;
+@group(1) @binding(1) var flow_sampler: sampler;
+
+// Maximum lean in radians, keeps blades from folding into the ground
+const MAX_LEAN: f32 = 1.4;
 
 fn hash(p: vec3<f32>) -> f32 {
     let p3 = fract(p * 0.1031);
@@ -81,6 +131,64 @@ fn remap(value: f32, in_min: f32, in_max: f32, out_min: f32, out_max: f32) -> f3
     return out_min + (value - in_min) * (out_max - out_min) / (in_max - in_min);
 }
 
+// Wind direction at a blade steered by the flow map, z holds a strength multiplier
+fn flow_wind(pos: vec2<f32>, base_dir: vec2<f32>) -> vec3<f32> {
+    if (wind.flow_map_mode == 0u) {
+        return vec3<f32>(base_dir, 1.0);
+    }
+
+    let uv = (pos + wind.field_half_size) / (2.0 * wind.field_half_size) + wind.flow_map_scroll * wind.time;
+    let flow = textureSampleLevel(flow_map, flow_sampler, uv, 0.0).rg * 2.0 - 1.0;
+    let flow_length = length(flow);
+
+    if (wind.flow_map_mode == 1u) {
+        // unpainted (zero) areas are sheltered from the wind
+        let scale = mix(1.0, min(flow_length, 1.0), wind.flow_map_strength);
+        if (flow_length < 0.01) {
+            return vec3<f32>(base_dir, scale);
+        }
+        let dir = mix(base_dir, flow / flow_length, wind.flow_map_strength);
+        if (length(dir) < 0.0001) {
+            return vec3<f32>(flow / flow_length, scale);
+        }
+        return vec3<f32>(normalize(dir), scale);
+    }
+
+    let biased = base_dir + flow * wind.flow_map_strength;
+    if (length(biased) < 0.0001) {
+        return vec3<f32>(base_dir, 1.0);
+    }
+    return vec3<f32>(normalize(biased), 1.0);
+}
+
+// Sum of all local emitters at a point on the ground, as a lean in radians
+fn emitter_wind(pos: vec2<f32>) -> vec2<f32> {
+    var total = vec2<f32>(0.0);
+    for (var i = 0u; i < wind.emitter_count; i++) {
+        let emitter = emitters[i];
+        let offset = pos - emitter.position;
+        let dist = length(offset);
+        if (dist >= emitter.radius || dist < 0.0001) {
+            continue;
+        }
+
+        let away = offset / dist;
+        let falloff = pow(1.0 - dist / emitter.radius, 2.0);
+
+        if (emitter.kind == 0u) {
+            total += away * emitter.strength * falloff;
+        } else if (emitter.kind == 1u) {
+            // soft edge over the outer part of the cone
+            let cone = smoothstep(emitter.cone_cos, mix(emitter.cone_cos, 1.0, 0.25), dot(away, emitter.direction));
+            total += emitter.direction * emitter.strength * falloff * cone;
+        } else {
+            let tangent = vec2<f32>(-away.y, away.x);
+            total += tangent * emitter.strength * falloff;
+        }
+    }
+    return total;
+}
+
 @compute @workgroup_size(64)
 fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
     let index = global_id.x;
@@ -97,41 +205,76 @@ fn main(@builtin(global_invocation_id) global_id: vec3<u32>) {
     
     let wind_dir = normalize(vec3<f32>(cos(wind.wind_angle), 0.0, sin(wind.wind_angle)));
     
-    // Large-scale wave pattern moving along wind direction
-    let wind_sample_pos = vec3<f32>(
-        base_pos.x * 0.05 + wind.time * wind_dir.x * 1.0,
-        0.0,
-        base_pos.z * 0.05 + wind.time * wind_dir.z * 1.0
-    );
-    let wind_strength = noise(wind_sample_pos)*0.8;
-    
-    // Add secondary wave
-    let wave2_pos = vec3<f32>(
-        base_pos.x * 0.08 - wind.time * wind_dir.x * 0.8,
-        0.0,
-        base_pos.z * 0.08 - wind.time * wind_dir.z * 0.8
+    // Scrolling noise layers, each moving along its own direction
+    var layered_wind = 0.0;
+    for (var i = 0u; i < wind.layer_count; i++) {
+        let layer = wind.layers[i];
+        let layer_angle = wind.wind_angle + layer.angle_offset;
+        let layer_dir = vec2<f32>(cos(layer_angle), sin(layer_angle));
+        let layer_pos = vec3<f32>(
+            base_pos.x * layer.scale + wind.time * layer_dir.x * layer.speed,
+            0.0,
+            base_pos.z * layer.scale + wind.time * layer_dir.y * layer.speed
+        );
+        layered_wind += noise(layer_pos) * layer.amplitude;
+    }
+
+    // Gusts are broad, slowly evolving patches travelling with the wind
+    let gust_pos = vec3<f32>(
+        base_pos.x * 0.02 + wind.time * wind_dir.x * 0.5,
+        wind.time * wind.gust_frequency,
+        base_pos.z * 0.02 + wind.time * wind_dir.z * 0.5
     );
-    let wave2 = noise(wave2_pos) * 0.5;
-    
-    // Combine waves
-    let combined_wind = wind_strength * 1.0;// + wave2;
+    let gust = 1.0 + wind.gust_amplitude * clamp(noise(gust_pos) * 2.0, 0.0, 1.0);
     
     // Faster turbulence
-    let animation_sample_pos = vec3<f32>(base_pos.x, base_pos.z, wind.time * 2.5);
-    let random_lean_animation = noise(animation_sample_pos) * (wind_strength * 0.6 + 0.125);
+    let animation_sample_pos = vec3<f32>(
+        base_pos.x * wind.turbulence_scale,
+        base_pos.z * wind.turbulence_scale,
+        wind.time * wind.turbulence_speed
+    );
+    let random_lean_animation = noise(animation_sample_pos) * (layered_wind * 0.6 + wind.turbulence_strength);
     
     let base_lean = remap(hash2, -0.0, 1.0, -0.2, 0.2);
     
     // Combine all factors for wavy motion
-    let lean_factor = combined_wind + random_lean_animation;// + base_lean;
+    let lean_factor = layered_wind * gust + random_lean_animation;// + base_lean;
     let wind_amount = lean_factor * wind.wind_strength;
     
+    let height = wind.blade_base_height * (0.8 + hash2 * 0.4);
+
+    // Damped spring integrated with semi-implicit Euler. The wind force is
+    // scaled so that a steady wind settles at a lean of wind_amount.
+    let stiffness = wind.stiffness * (0.8 + hash3 * 0.4);
+    var state = state_in[index];
+    let flow = flow_wind(base_pos.xz, wind_dir.xz);
+    let local_wind = emitter_wind(base_pos.xz);
+    let wind_force = (flow.xy * wind_amount * flow.z + local_wind) * stiffness;
+    var accel = wind_force - stiffness * state.tip_offset - wind.damping * state.velocity;
+
+    // Gravity pulls a leaning blade further over, taller blades droop more
+    let lean = length(state.tip_offset);
+    if (lean > 0.0001) {
+        let droop = wind.gravity * height / wind.blade_base_height;
+        accel += state.tip_offset / lean * sin(lean) * droop;
+    }
+
+    state.velocity += accel * wind.delta_time;
+    state.tip_offset += state.velocity * wind.delta_time;
+
+    let new_lean = length(state.tip_offset);
+    if (new_lean > MAX_LEAN) {
+        state.tip_offset *= MAX_LEAN / new_lean;
+    }
+    state_out[index] = state;
+    
     var grass = input_positions[index];
     grass.position = base_pos;
-    grass.wind_sway = wind_amount;
-    grass.height = wind.blade_base_height * (0.8 + hash2 * 0.4);
-    grass.width = 0.9 + hash3 * 0.2;
-    grass.bend = 1.2 + blade_hash * 0.5;
+    grass.wind_sway = length(state.tip_offset);
+    grass.sway_angle = atan2(state.tip_offset.y, state.tip_offset.x);
+    grass.height = height;
+    grass.width = wind.blade_width * (0.9 + hash3 * 0.2);
+    grass.bend = wind.blade_bend * (1.2 + blade_hash * 0.5);
     grass.tilt = base_lean;
     
     let facing_angle = blade_hash * 6.28318;
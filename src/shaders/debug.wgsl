@@ -0,0 +1,46 @@
+// Debug view selection, prepended to the grass and ground shaders
+
+struct DebugUniforms {
+    view: u32,
+    // wireframe drawn from barycentrics, set when the adapter has no line polygon mode
+    barycentric_wireframe: u32,
+    // camera distances where the grass LOD levels start
+    lod_distances: vec2<f32>,
+    clump_size: f32,
+}
+
+@group(0) @binding(6)
+var<uniform> debug: DebugUniforms;
+
+const DEBUG_VIEW_NONE: u32 = 0u;
+const DEBUG_VIEW_NORMALS: u32 = 1u;
+const DEBUG_VIEW_WIND_SWAY: u32 = 2u;
+const DEBUG_VIEW_BLADE_HASH: u32 = 3u;
+const DEBUG_VIEW_SPECIES: u32 = 4u;
+const DEBUG_VIEW_HEIGHT_FACTOR: u32 = 5u;
+const DEBUG_VIEW_OVERDRAW: u32 = 6u;
+const DEBUG_VIEW_LOD_LEVEL: u32 = 7u;
+const DEBUG_VIEW_CLUMP_ID: u32 = 8u;
+
+// added once per fragment, red saturates first, then green and blue
+const OVERDRAW_HEAT: vec3<f32> = vec3<f32>(0.12, 0.04, 0.015);
+
+// corner of the triangle in an unindexed draw, for the barycentric wireframe
+fn triangle_barycentric(vertex_index: u32) -> vec3<f32> {
+    let corner = vertex_index % 3u;
+    return vec3<f32>(f32(corner == 0u), f32(corner == 1u), f32(corner == 2u));
+}
+
+// true for fragments more than half a pixel away from every edge of their triangle,
+// so edges shared by two triangles come out about a pixel wide
+fn off_wireframe(barycentric: vec3<f32>) -> bool {
+    let distance = barycentric / max(fwidth(barycentric), vec3<f32>(1e-5));
+    return debug.barycentric_wireframe != 0u && min(distance.x, min(distance.y, distance.z)) > 0.5;
+}
+
+// distinct colours for small ids
+fn debug_palette(id: u32) -> vec3<f32> {
+    let hue = fract(f32(id) * 0.618034);
+    let rgb = abs(fract(vec3<f32>(hue) + vec3<f32>(0.0, 2.0, 1.0) / 3.0) * 6.0 - 3.0) - 1.0;
+    return clamp(rgb, vec3<f32>(0.0), vec3<f32>(1.0));
+}
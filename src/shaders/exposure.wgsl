@@ -0,0 +1,91 @@
+// Auto-exposure: a log luminance histogram of the HDR target, reduced to a temporally
+// adapted average luminance
+
+const BIN_COUNT: u32 = 256u;
+
+struct ExposureUniforms {
+    min_log_luminance: f32,
+    log_luminance_range: f32,
+    delta_time: f32,
+    adaptation_speed: f32,
+    pixel_count: u32,
+    _padding1: u32,
+    _padding2: u32,
+    _padding3: u32,
+}
+
+struct Luminance {
+    average: f32,
+    _padding1: f32,
+    _padding2: f32,
+    _padding3: f32,
+}
+
+@group(0) @binding(0)
+var hdr_texture: texture_2d<f32>;
+
+@group(0) @binding(1)
+var<storage, read_write> histogram: array<atomic<u32>, BIN_COUNT>;
+
+@group(0) @binding(2)
+var<storage, read_write> luminance: Luminance;
+
+@group(0) @binding(3)
+var<uniform> exposure: ExposureUniforms;
+
+var<workgroup> local_bins: array<atomic<u32>, BIN_COUNT>;
+var<workgroup> weighted_counts: array<u32, BIN_COUNT>;
+
+// bin 0 holds (near) black pixels, they are left out of the average
+fn luminance_bin(color: vec3<f32>) -> u32 {
+    let lum = dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+    if (lum < 0.0001) {
+        return 0u;
+    }
+    let log_lum = saturate((log2(lum) - exposure.min_log_luminance) / exposure.log_luminance_range);
+    return u32(log_lum * 254.0 + 1.0);
+}
+
+@compute @workgroup_size(16, 16)
+fn build_histogram(
+    @builtin(global_invocation_id) global_id: vec3<u32>,
+    @builtin(local_invocation_index) local_index: u32,
+) {
+    atomicStore(&local_bins[local_index], 0u);
+    workgroupBarrier();
+
+    let size = textureDimensions(hdr_texture);
+    if (global_id.x < size.x && global_id.y < size.y) {
+        let color = textureLoad(hdr_texture, vec2<i32>(global_id.xy), 0).rgb;
+        atomicAdd(&local_bins[luminance_bin(color)], 1u);
+    }
+    workgroupBarrier();
+
+    atomicAdd(&histogram[local_index], atomicLoad(&local_bins[local_index]));
+}
+
+@compute @workgroup_size(256)
+fn average_luminance(@builtin(local_invocation_index) local_index: u32) {
+    let count = atomicLoad(&histogram[local_index]);
+    weighted_counts[local_index] = count * local_index;
+    atomicStore(&histogram[local_index], 0u);
+    workgroupBarrier();
+
+    for (var stride = BIN_COUNT / 2u; stride > 0u; stride >>= 1u) {
+        if (local_index < stride) {
+            weighted_counts[local_index] += weighted_counts[local_index + stride];
+        }
+        workgroupBarrier();
+    }
+
+    if (local_index == 0u) {
+        // `count` is the number of black pixels for thread 0
+        let lit_pixels = max(f32(exposure.pixel_count) - f32(count), 1.0);
+        let weighted_bin = f32(weighted_counts[0]) / lit_pixels - 1.0;
+        let log_average = weighted_bin / 254.0 * exposure.log_luminance_range + exposure.min_log_luminance;
+        let target_luminance = exp2(log_average);
+
+        let blend = 1.0 - exp(-exposure.delta_time * exposure.adaptation_speed);
+        luminance.average += (target_luminance - luminance.average) * blend;
+    }
+}
@@ -8,6 +8,31 @@ var<uniform> wind_data: vec4<f32>;
 @group(0) @binding(2)
 var<uniform> camera_pos: vec3<f32>;
 
+const MAX_GRASS_SPECIES: u32 = 4u;
+
+struct Species {
+    transmission_color: vec3<f32>,
+    thickness: f32,
+    base_color: vec3<f32>,
+    tip_color: vec3<f32>,
+}
+
+struct Material {
+    species: array<Species, 4>,
+    species_count: u32,
+    // 0 is spring, 1 is autumn
+    season: f32,
+    variation_strength: f32,
+    variation_scale: f32,
+    dry_color: vec3<f32>,
+    dry_patch_coverage: f32,
+    autumn_color: vec3<f32>,
+    dry_patch_scale: f32,
+}
+
+@group(0) @binding(5)
+var<uniform> material: Material;
+
 struct VertexInput {
     @location(0) position: vec3<f32>,
     @location(1) instance_pos: vec3<f32>,
@@ -18,6 +43,11 @@ struct VertexInput {
     @location(6) tilt: f32,
     @location(7) facing: vec2<f32>,
     @location(8) blade_hash: f32,
+    @location(9) sway_angle: f32,
+    // wind state of the previous frame, for motion vectors
+    @location(10) previous_wind_sway: f32,
+    @location(11) previous_sway_angle: f32,
+    @builtin(vertex_index) vertex_index: u32,
 }
 
 struct VertexOutput {
@@ -26,6 +56,26 @@ struct VertexOutput {
     @location(1) blade_hash: f32,
     @location(2) normal: vec3<f32>,
     @location(3) world_pos: vec3<f32>,
+    // -1 on the left edge of the blade, 1 on the right
+    @location(4) side: f32,
+    @location(5) current_clip: vec4<f32>,
+    @location(6) previous_clip: vec4<f32>,
+    @location(7) @interpolate(flat) species: u32,
+    // large scale colour variation and dryness of the blade, both 0..1
+    @location(8) @interpolate(flat) variation: f32,
+    @location(9) @interpolate(flat) dryness: f32,
+    @location(10) @interpolate(flat) wind_sway: f32,
+    @location(11) barycentric: vec3<f32>,
+    // only filled in for the LOD level and clump ID debug views
+    @location(12) @interpolate(flat) lod_level: u32,
+    @location(13) @interpolate(flat) clump_id: u32,
+}
+
+struct BladeVertex {
+    world_pos: vec3<f32>,
+    // world_pos after the view-space thickening
+    display_pos: vec3<f32>,
+    normal: vec3<f32>,
 }
 
 // Todo:
@@ -100,22 +150,146 @@ fn ease_out(x: f32, t: f32) -> f32 {
     return 1.0 - pow(1.0 - x, t);
 }
 
-fn hemi_light(normal: vec3<f32>, ground_colour: vec3<f32>, sky_colour: vec3<f32>) -> vec3<f32> {
-    return mix(ground_colour, sky_colour, 0.5 * normal.y + 0.5);
+// Light passing through the blade and leaving towards the viewer, strongest when looking
+// into the sun. The exit direction is bent by the normal and thicker blades absorb more.
+fn translucency(normal: vec3<f32>, view_dir: vec3<f32>, light_dir: vec3<f32>, thickness: f32, transmission_color: vec3<f32>) -> vec3<f32> {
+    let exit_dir = normalize(light_dir + normal * lighting.translucency_distortion);
+    let through = pow(saturate(dot(view_dir, -exit_dir)), lighting.translucency_power);
+    let transmittance = exp(-thickness * 2.0);
+    return transmission_color * through * transmittance * lighting.translucency_strength;
 }
 
-fn lambert_light(normal: vec3<f32>, view_dir: vec3<f32>, light_dir: vec3<f32>, light_colour: vec3<f32>) -> vec3<f32> {
+fn lambert_light(normal: vec3<f32>, view_dir: vec3<f32>, light_dir: vec3<f32>, light_colour: vec3<f32>, species: Species, height_factor: f32) -> vec3<f32> {
     let wrap = 0.8;
     let dot_nl = saturate((dot(normal, light_dir) + wrap) / (wrap + 1.0));
     var lighting = vec3<f32>(dot_nl);
     
-    let backlight = saturate((dot(view_dir, light_dir) + wrap) / (wrap + 1.0));
-    let scatter = vec3<f32>(pow(backlight, 2.0));
-    
-    lighting += scatter;
+    // blades thin out towards the tip
+    let thickness = species.thickness * mix(1.0, 0.4, height_factor);
+    lighting += translucency(normal, view_dir, light_dir, thickness, species.transmission_color);
     return lighting * light_colour;
 }
 
+fn hash12(p: vec2<f32>) -> f32 {
+    var p3 = fract(vec3<f32>(p.xyx) * 0.1031);
+    p3 += dot(p3, p3.yzx + 33.33);
+    return fract((p3.x + p3.y) * p3.z);
+}
+
+// value noise in 0..1
+fn value_noise(p: vec2<f32>) -> f32 {
+    let i = floor(p);
+    let f = fract(p);
+    let u = f * f * (3.0 - 2.0 * f);
+    return mix(
+        mix(hash12(i), hash12(i + vec2<f32>(1.0, 0.0)), u.x),
+        mix(hash12(i + vec2<f32>(0.0, 1.0)), hash12(i + vec2<f32>(1.0, 1.0)), u.x),
+        u.y
+    );
+}
+
+fn fbm(p: vec2<f32>) -> f32 {
+    var value = 0.0;
+    var amplitude = 0.5;
+    var position = p;
+    for (var i = 0; i < 3; i++) {
+        value += value_noise(position) * amplitude;
+        position *= 2.03;
+        amplitude *= 0.5;
+    }
+    return value / 0.875;
+}
+
+// the biome noise pulls neighbouring blades towards the same species
+fn blade_species(blade_hash: f32, biome: f32) -> u32 {
+    let count = max(material.species_count, 1u);
+    let pick = fract(blade_hash * 91.7) * 0.5 + biome * 0.5;
+    return min(u32(pick * f32(count)), count - 1u);
+}
+
+// dry patches spread over more of the field as the season goes on
+fn blade_dryness(instance_pos: vec3<f32>) -> f32 {
+    let coverage = saturate(material.dry_patch_coverage + material.season * 0.5);
+    let patches = fbm(instance_pos.xz * material.dry_patch_scale + vec2<f32>(17.3, -4.1));
+    // the noise clusters around 0.5, so the threshold only spans the middle of its range
+    let threshold = 0.5 + (0.5 - coverage) * 0.6;
+    return smoothstep(threshold, threshold + 0.08, patches);
+}
+
+fn blade_color(species: Species, height_factor: f32, blade_hash: f32, variation: f32, dryness: f32) -> vec3<f32> {
+    var color = mix(species.base_color, species.tip_color, height_factor);
+    color *= 0.95 + blade_hash * 0.1;
+
+    // warmer and brighter on one end of the noise, cooler and darker on the other
+    let shift = (variation * 2.0 - 1.0) * material.variation_strength;
+    color *= max(vec3<f32>(1.0) + shift * vec3<f32>(0.3, 0.15, -0.2), vec3<f32>(0.0));
+
+    let autumn = material.autumn_color * mix(0.6, 1.0, height_factor);
+    color = mix(color, autumn, material.season * 0.85);
+
+    let dry = material.dry_color * mix(0.7, 1.0, height_factor);
+    return mix(color, dry, dryness);
+}
+
+// distance band of the blade root, 0 up to the first of `lod_distances`
+fn blade_lod_level(instance_pos: vec3<f32>) -> u32 {
+    let distance = length(camera_pos - instance_pos);
+    return u32(distance >= debug.lod_distances.x) + u32(distance >= debug.lod_distances.y);
+}
+
+// blades belong to the nearest of the jittered cell centres `clump_size` apart
+fn blade_clump_id(instance_pos: vec3<f32>) -> u32 {
+    let p = instance_pos.xz / debug.clump_size;
+    let cell = floor(p);
+    var nearest = cell;
+    var nearest_distance = 1e9;
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let neighbour = cell + vec2<f32>(f32(x), f32(y));
+            let centre = neighbour + vec2<f32>(hash12(neighbour), hash12(neighbour + 71.3));
+            let distance = dot(p - centre, p - centre);
+            if (distance < nearest_distance) {
+                nearest = neighbour;
+                nearest_distance = distance;
+            }
+        }
+    }
+    return u32(hash12(nearest + 13.7) * 4096.0);
+}
+
+// matches MAX_LEAN in compute.wgsl
+const DEBUG_MAX_WIND_SWAY: f32 = 1.4;
+
+fn grass_debug_color(in: VertexOutput) -> vec3<f32> {
+    switch debug.view {
+        case DEBUG_VIEW_NORMALS: {
+            return normalize(in.normal) * 0.5 + 0.5;
+        }
+        case DEBUG_VIEW_WIND_SWAY: {
+            let sway = saturate(in.wind_sway / DEBUG_MAX_WIND_SWAY);
+            return mix(vec3<f32>(0.05, 0.1, 0.8), vec3<f32>(0.9, 0.1, 0.05), sway);
+        }
+        case DEBUG_VIEW_BLADE_HASH: {
+            return vec3<f32>(in.blade_hash);
+        }
+        case DEBUG_VIEW_SPECIES: {
+            return debug_palette(in.species);
+        }
+        case DEBUG_VIEW_HEIGHT_FACTOR: {
+            return vec3<f32>(in.height_factor);
+        }
+        case DEBUG_VIEW_LOD_LEVEL: {
+            return debug_palette(in.lod_level);
+        }
+        case DEBUG_VIEW_CLUMP_ID: {
+            return debug_palette(in.clump_id);
+        }
+        default: {
+            return OVERDRAW_HEAT;
+        }
+    }
+}
+
 fn phong_specular(normal: vec3<f32>, light_dir: vec3<f32>, view_dir: vec3<f32>) -> vec3<f32> {
     let dot_nl = saturate(dot(normal, light_dir));
     let r = normalize(reflect(-light_dir, normal));
@@ -126,9 +300,8 @@ fn phong_specular(normal: vec3<f32>, light_dir: vec3<f32>, view_dir: vec3<f32>)
     return specular;
 }
 
-@vertex
-fn vs_main(in: VertexInput) -> VertexOutput {
-    var out: VertexOutput;
+// `eye` is the viewer the blade thickens towards, a point with w = 1 or a direction with w = 0
+fn blade_vertex(in: VertexInput, wind_sway: f32, sway_angle: f32, eye: vec4<f32>) -> BladeVertex {
     
      // Determine which side of blade
     let x_side = sign(in.position.x);
@@ -143,10 +316,9 @@ fn vs_main(in: VertexInput) -> VertexOutput {
     scaled_pos.x *= in.width * final_width;
 
     
-    let wind_angle = wind_data.z;
-    let wind_axis = vec3<f32>(cos(wind_angle + 1.5708), 0.0, sin(wind_angle + 1.5708));
+    let wind_axis = vec3<f32>(cos(sway_angle + 1.5708), 0.0, sin(sway_angle + 1.5708));
     
-    let lean_factor = in.wind_sway;
+    let lean_factor = wind_sway;
     let wind_lean_angle = lean_factor * 1.5 * height_factor * in.bend;
     
     let p0 = vec3<f32>(0.0, 0.0, 0.0);
@@ -173,7 +345,7 @@ fn vs_main(in: VertexInput) -> VertexOutput {
     let world_pos = grass_local_pos + in.instance_pos;
     
     // View-space thickening
-    let view_dir = normalize(camera_pos - world_pos);
+    let view_dir = normalize(eye.xyz - world_pos * eye.w);
     
     let view_dot_right = abs(dot(blade_right, view_dir));
     
@@ -186,21 +358,60 @@ fn vs_main(in: VertexInput) -> VertexOutput {
     var thickened_pos = world_pos;
     thickened_pos += blade_right * view_space_thicken_factor * x_side * in.width * final_width * THICKEN_AMOUNT * THICKEN_ENABLED;
     
-    out.clip_position = view_proj * vec4<f32>(thickened_pos, 1.0);
+    var blade: BladeVertex;
+    blade.world_pos = world_pos;
+    blade.display_pos = thickened_pos;
+    blade.normal = grass_normal;
+    return blade;
+}
+
+@vertex
+fn vs_main(in: VertexInput) -> VertexOutput {
+    var out: VertexOutput;
+
+    let eye = vec4<f32>(camera_pos, 1.0);
+    let blade = blade_vertex(in, in.wind_sway, in.sway_angle, eye);
+    // the same vertex bent by last frame's wind
+    let previous = blade_vertex(in, in.previous_wind_sway, in.previous_sway_angle, eye);
+
+    out.clip_position = view_proj * vec4<f32>(blade.display_pos, 1.0);
     
-    out.height_factor = height_factor;
+    out.height_factor = in.position.y;
     out.blade_hash = in.blade_hash;
-    out.normal = grass_normal;
-    out.world_pos = world_pos;
+    out.normal = blade.normal;
+    out.world_pos = blade.world_pos;
+    out.side = sign(in.position.x);
+    out.current_clip = temporal.view_proj * vec4<f32>(blade.display_pos, 1.0);
+    out.previous_clip = temporal.previous_view_proj * vec4<f32>(previous.display_pos, 1.0);
+    let biome = fbm(in.instance_pos.xz * material.variation_scale);
+    out.species = blade_species(in.blade_hash, biome);
+    out.variation = biome;
+    out.dryness = blade_dryness(in.instance_pos);
+    out.wind_sway = in.wind_sway;
+    out.barycentric = triangle_barycentric(in.vertex_index);
+    out.lod_level = 0u;
+    out.clump_id = 0u;
+    if (debug.view == DEBUG_VIEW_LOD_LEVEL) {
+        out.lod_level = blade_lod_level(in.instance_pos);
+    } else if (debug.view == DEBUG_VIEW_CLUMP_ID) {
+        out.clump_id = blade_clump_id(in.instance_pos);
+    }
     
     return out;
 }
 
+// Depth only for the shadow cascades, thickened towards the sun so the shadow does not follow the camera
+@vertex
+fn vs_shadow(in: VertexInput) -> @builtin(position) vec4<f32> {
+    let blade = blade_vertex(in, in.wind_sway, in.sway_angle, vec4<f32>(lighting.sun_direction, 0.0));
+    return view_proj * vec4<f32>(blade.display_pos, 1.0);
+}
+
 @fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+fn fs_main(in: VertexOutput) -> FragmentOutput {
     let view_dir = normalize(camera_pos - in.world_pos);
-    let light_dir = normalize(vec3<f32>(-1.0, 0.5, 1.0));
-    let light_color = vec3<f32>(1.0, 1.0, 0.9);
+    let light_dir = lighting.sun_direction;
+    let light_color = sun_light();
     
     // Flip normal for back-facing fragments (two-sided lighting)
     var normal = normalize(in.normal);
@@ -208,30 +419,36 @@ fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
         normal = -normal;
     }
     
-    let color_variation = in.blade_hash * 0.1;
     //let brightness = in.height_factor * 0.4 + 0.6;
 
-    let c1 = vec3<f32>(1.0, 1.0, 0.5);
-    let c2 = vec3<f32>(0.05, 0.05, 0.25);
+    let shadow = shadow_factor(in.world_pos, normal, camera_pos);
 
-    let ambient_light = hemi_light(normal, c2, c1);
-    let diffuse_light = lambert_light(normal, view_dir, light_dir, light_color);
-    let specular = phong_specular(normal, light_dir, view_dir);
-    let lighting = ambient_light * 0.6 + diffuse_light * 0.4;
+    let ambient = ambient_light(normal);
+    let species = material.species[min(in.species, MAX_GRASS_SPECIES - 1u)];
+    let diffuse_light = lambert_light(normal, view_dir, light_dir, light_color, species, in.height_factor) * shadow;
+    let specular = phong_specular(normal, light_dir, view_dir) * light_color * shadow;
+    let light = ambient * 0.6 + diffuse_light * 0.4;
     //let base_color = vec3<f32>(0.1, 0.5, 0.2);
     
-    let base_color = vec3<f32>(
-        0.3 + color_variation,
-        0.8 - color_variation * 0.5,
-        0.3 + color_variation * 0.3
-    );
+    let base_color = blade_color(species, in.height_factor, in.blade_hash, in.variation, in.dryness);
 
     // fake grass AO
     let ao = remap(pow(in.height_factor, 2.0), 0.0, 1.0, 0.25, 1.0);
     
-    var final_color = base_color * lighting + specular * 0.125;
+    var final_color = base_color * light + specular * 0.125;
     final_color *= ao;
+    final_color = apply_fog(final_color, in.world_pos, camera_pos);
+    if (debug.view != DEBUG_VIEW_NONE) {
+        final_color = grass_debug_color(in);
+    }
 
-    let normal_color = normalize(in.normal) * 0.5 + 0.5;
-    return vec4<f32>(final_color, 1.0);
+    // fade out across the last pixel of the edges, turned into coverage with MSAA
+    let edge_alpha = saturate((1.0 - abs(in.side)) / max(fwidth(in.side), 1e-5) + 0.5);
+    if (off_wireframe(in.barycentric)) {
+        discard;
+    }
+    var out: FragmentOutput;
+    out.color = vec4<f32>(final_color, edge_alpha);
+    out.velocity = motion_vector(in.current_clip, in.previous_clip);
+    return out;
 }
\ No newline at end of file
@@ -1,13 +1,20 @@
 @group(0) @binding(0)
 var<uniform> view_proj: mat4x4<f32>;
 
+@group(0) @binding(2)
+var<uniform> camera_pos: vec3<f32>;
+
 struct VertexInput {
     @location(0) position: vec3<f32>,
+    @builtin(vertex_index) vertex_index: u32,
 }
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
     @location(0) world_pos: vec3<f32>,
+    @location(1) current_clip: vec4<f32>,
+    @location(2) previous_clip: vec4<f32>,
+    @location(3) barycentric: vec3<f32>,
 }
 
 @vertex
@@ -15,14 +22,38 @@ fn vs_main(in: VertexInput) -> VertexOutput {
     var out: VertexOutput;
     out.clip_position = view_proj * vec4<f32>(in.position, 1.0);
     out.world_pos = in.position;
+    // the ground does not move, only the camera does
+    out.current_clip = temporal.view_proj * vec4<f32>(in.position, 1.0);
+    out.previous_clip = temporal.previous_view_proj * vec4<f32>(in.position, 1.0);
+    out.barycentric = triangle_barycentric(in.vertex_index);
     return out;
 }
 
 @fragment
-fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+fn fs_main(in: VertexOutput) -> FragmentOutput {
     let base_color = vec3<f32>(0.05, 0.15, 0.05);
     
     // maybe add some variation at a later point
 
-    return vec4<f32>(base_color, 1.0);
+    let normal = vec3<f32>(0.0, 1.0, 0.0);
+    let shadow = shadow_factor(in.world_pos, normal, camera_pos);
+    let diffuse = saturate(dot(normal, lighting.sun_direction)) * sun_light() * shadow;
+    let light = ambient_light(normal) * 0.6 + diffuse * 0.4;
+
+    if (off_wireframe(in.barycentric)) {
+        discard;
+    }
+
+    var out: FragmentOutput;
+    out.color = vec4<f32>(apply_fog(base_color * light, in.world_pos, camera_pos), 1.0);
+    // a dark backdrop keeps the blades readable in the debug views
+    if (debug.view == DEBUG_VIEW_NORMALS) {
+        out.color = vec4<f32>(normal * 0.5 + 0.5, 1.0);
+    } else if (debug.view == DEBUG_VIEW_OVERDRAW) {
+        out.color = vec4<f32>(0.0, 0.0, 0.0, 1.0);
+    } else if (debug.view != DEBUG_VIEW_NONE) {
+        out.color = vec4<f32>(0.02, 0.02, 0.02, 1.0);
+    }
+    out.velocity = motion_vector(in.current_clip, in.previous_clip);
+    return out;
 }
\ No newline at end of file
@@ -0,0 +1,55 @@
+// Scene lighting, prepended to the grass and ground shaders
+
+struct Lighting {
+    sun_direction: vec3<f32>,
+    sun_intensity: f32,
+    sun_color: vec3<f32>,
+    ambient_intensity: f32,
+    sky_color: vec3<f32>,
+    ground_color: vec3<f32>,
+    fog_color: vec3<f32>,
+    fog_density: f32,
+    fog_height_falloff: f32,
+    fog_sun_scattering: f32,
+    translucency_distortion: f32,
+    translucency_power: f32,
+    translucency_strength: f32,
+}
+
+@group(0) @binding(3)
+var<uniform> lighting: Lighting;
+
+fn hemi_light(normal: vec3<f32>, ground_colour: vec3<f32>, sky_colour: vec3<f32>) -> vec3<f32> {
+    return mix(ground_colour, sky_colour, 0.5 * normal.y + 0.5);
+}
+
+fn ambient_light(normal: vec3<f32>) -> vec3<f32> {
+    return hemi_light(normal, lighting.ground_color, lighting.sky_color) * lighting.ambient_intensity;
+}
+
+fn sun_light() -> vec3<f32> {
+    return lighting.sun_color * lighting.sun_intensity;
+}
+
+// Exponential height fog between the camera and world_pos, also brightens towards the sun
+fn apply_fog(color: vec3<f32>, world_pos: vec3<f32>, camera_position: vec3<f32>) -> vec3<f32> {
+    if (lighting.fog_density <= 0.0) {
+        return color;
+    }
+
+    let ray = world_pos - camera_position;
+    let distance = length(ray);
+    let view_dir = ray / max(distance, 1e-4);
+
+    // density integrated along the ray, falls back to a constant density for level rays
+    let falloff = lighting.fog_height_falloff * ray.y;
+    var optical_depth = lighting.fog_density * exp(-lighting.fog_height_falloff * camera_position.y) * distance;
+    if (abs(falloff) > 1e-4) {
+        optical_depth *= (1.0 - exp(-falloff)) / falloff;
+    }
+    let fog_amount = 1.0 - exp(-optical_depth);
+
+    let sun_amount = pow(saturate(dot(view_dir, lighting.sun_direction)), 8.0) * lighting.fog_sun_scattering;
+    let fog_color = mix(lighting.fog_color, sun_light(), sun_amount);
+    return mix(color, fog_color, fog_amount);
+}
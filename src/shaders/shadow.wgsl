@@ -0,0 +1,63 @@
+// Cascaded sun shadows, prepended to the grass and ground shaders
+
+struct ShadowUniforms {
+    light_view_proj: array<mat4x4<f32>, 4>,
+    split_distances: vec4<f32>,
+    camera_forward: vec4<f32>,
+    cascade_count: u32,
+    texel_size: f32,
+    _padding1: f32,
+    _padding2: f32,
+}
+
+@group(1) @binding(0)
+var<uniform> shadow: ShadowUniforms;
+
+@group(1) @binding(1)
+var shadow_map: texture_depth_2d_array;
+
+@group(1) @binding(2)
+var shadow_sampler: sampler_comparison;
+
+// 1.0 = fully lit, 0.0 = fully shadowed
+fn shadow_factor(world_pos: vec3<f32>, normal: vec3<f32>, camera_position: vec3<f32>) -> f32 {
+    let view_depth = dot(world_pos - camera_position, shadow.camera_forward.xyz);
+
+    var cascade = 0u;
+    loop {
+        if (cascade >= shadow.cascade_count || view_depth < shadow.split_distances[cascade]) {
+            break;
+        }
+        cascade += 1u;
+    }
+    if (cascade >= shadow.cascade_count) {
+        return 1.0;
+    }
+
+    // push the lookup along the normal, larger cascades need a larger offset
+    let cascade_scale = shadow.split_distances[cascade] / shadow.split_distances[0];
+    let offset_pos = world_pos + normal * shadow.texel_size * 8.0 * cascade_scale;
+
+    let light_pos = shadow.light_view_proj[cascade] * vec4<f32>(offset_pos, 1.0);
+    let ndc = light_pos.xyz / light_pos.w;
+    let uv = ndc.xy * vec2<f32>(0.5, -0.5) + 0.5;
+    if (any(uv < vec2<f32>(0.0)) || any(uv > vec2<f32>(1.0)) || ndc.z > 1.0) {
+        return 1.0;
+    }
+
+    // 3x3 PCF
+    var lit = 0.0;
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let offset = vec2<f32>(f32(x), f32(y)) * shadow.texel_size;
+            lit += textureSampleCompareLevel(shadow_map, shadow_sampler, uv + offset, i32(cascade), ndc.z);
+        }
+    }
+    lit /= 9.0;
+
+    // fade out towards the end of the last cascade
+    let fade_start = shadow.split_distances[shadow.cascade_count - 1u] * 0.9;
+    let fade = saturate((view_depth - fade_start) / (shadow.split_distances[shadow.cascade_count - 1u] - fade_start));
+    return mix(lit, 1.0, fade);
+}
+
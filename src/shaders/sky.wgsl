@@ -0,0 +1,166 @@
+// Full-screen sky, drawn before the ground
+
+struct SkyUniforms {
+    inverse_view_proj: mat4x4<f32>,
+    // unjittered camera matrices of this and the previous frame
+    view_proj: mat4x4<f32>,
+    previous_view_proj: mat4x4<f32>,
+    camera_position: vec4<f32>,
+    sun_direction: vec3<f32>,
+    model: u32,
+    horizon_color: vec3<f32>,
+    turbidity: f32,
+    zenith_color: vec3<f32>,
+    rayleigh: f32,
+    mie_coefficient: f32,
+    mie_directional_g: f32,
+    exposure: f32,
+    orthographic: u32,
+    // above zero the atmosphere is compressed into display range, for the clamp tonemapper
+    clamped_exposure: f32,
+}
+
+@group(0) @binding(0)
+var<uniform> sky: SkyUniforms;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) ndc: vec2<f32>,
+}
+
+const PI: f32 = 3.141592653589793;
+const MODEL_GRADIENT: u32 = 1u;
+
+// Preetham style analytic atmosphere, see "A Practical Analytic Model for Daylight"
+const TOTAL_RAYLEIGH: vec3<f32> = vec3<f32>(5.804542996261093e-6, 1.3562911419845635e-5, 3.0265902468824876e-5);
+const MIE_CONST: vec3<f32> = vec3<f32>(1.8399918514433978e14, 2.7798023919660528e14, 4.0790479543861094e14);
+const RAYLEIGH_ZENITH_LENGTH: f32 = 8.4e3;
+const MIE_ZENITH_LENGTH: f32 = 1.25e3;
+// sun light fades out this far below the horizon (radians from the zenith)
+const CUTOFF_ANGLE: f32 = 1.6110731556870734;
+const STEEPNESS: f32 = 1.5;
+const SUN_ILLUMINANCE: f32 = 1000.0;
+const SUN_ANGULAR_DIAMETER_COS: f32 = 0.99995667694;
+
+// one triangle covering the screen
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    let ndc = uv * 2.0 - 1.0;
+
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(ndc, 0.0, 1.0);
+    out.ndc = ndc;
+    return out;
+}
+
+fn sun_intensity(zenith_angle_cos: f32) -> f32 {
+    let angle = acos(clamp(zenith_angle_cos, -1.0, 1.0));
+    return SUN_ILLUMINANCE * max(0.0, 1.0 - exp(-((CUTOFF_ANGLE - angle) / STEEPNESS)));
+}
+
+fn total_mie(turbidity: f32) -> vec3<f32> {
+    let c = (0.2 * turbidity) * 10e-18;
+    return 0.434 * c * MIE_CONST;
+}
+
+fn henyey_greenstein(cos_theta: f32, g: f32) -> f32 {
+    let g2 = g * g;
+    return (1.0 / (4.0 * PI)) * ((1.0 - g2) / pow(1.0 - 2.0 * g * cos_theta + g2, 1.5));
+}
+
+fn atmosphere(direction: vec3<f32>) -> vec3<f32> {
+    let up = vec3<f32>(0.0, 1.0, 0.0);
+    let sun = normalize(sky.sun_direction);
+    let sun_e = sun_intensity(dot(sun, up));
+    let sun_fade = 1.0 - clamp(1.0 - exp(sun.y), 0.0, 1.0);
+
+    let rayleigh_coefficient = sky.rayleigh - (1.0 - sun_fade);
+    let beta_r = TOTAL_RAYLEIGH * rayleigh_coefficient;
+    let beta_m = total_mie(sky.turbidity) * sky.mie_coefficient;
+
+    // optical length along the view ray
+    let zenith_angle = acos(max(0.0, dot(up, direction)));
+    let inverse = 1.0 / (cos(zenith_angle) + 0.15 * pow(93.885 - zenith_angle * 180.0 / PI, -1.253));
+    let s_r = RAYLEIGH_ZENITH_LENGTH * inverse;
+    let s_m = MIE_ZENITH_LENGTH * inverse;
+    let extinction = exp(-(beta_r * s_r + beta_m * s_m));
+
+    // in-scattering
+    let cos_theta = dot(direction, sun);
+    let rayleigh_phase = (3.0 / (16.0 * PI)) * (1.0 + pow(cos_theta * 0.5 + 0.5, 2.0));
+    let mie_phase = henyey_greenstein(cos_theta, sky.mie_directional_g);
+    let scattering = (beta_r * rayleigh_phase + beta_m * mie_phase) / (beta_r + beta_m);
+
+    var in_scatter = pow(sun_e * scattering * (1.0 - extinction), vec3<f32>(1.5));
+    in_scatter *= mix(
+        vec3<f32>(1.0),
+        pow(sun_e * scattering * extinction, vec3<f32>(0.5)),
+        clamp(pow(1.0 - dot(up, sun), 5.0), 0.0, 1.0),
+    );
+
+    // night sky and sun disk
+    var base = vec3<f32>(0.1) * extinction;
+    let sun_disk = smoothstep(SUN_ANGULAR_DIAMETER_COS, SUN_ANGULAR_DIAMETER_COS + 0.00002, cos_theta);
+    base += sun_e * 19000.0 * extinction * sun_disk;
+
+    let color = (in_scatter + base) * 0.04 + vec3<f32>(0.0, 0.0003, 0.00075);
+    if (sky.clamped_exposure > 0.0) {
+        return 1.0 - exp(-color * sky.clamped_exposure);
+    }
+    return color * sky.exposure;
+}
+
+fn gradient(direction: vec3<f32>) -> vec3<f32> {
+    let height = sqrt(max(direction.y, 0.0));
+    return mix(sky.horizon_color, sky.zenith_color, height);
+}
+
+struct FragmentOutput {
+    @location(0) color: vec4<f32>,
+    @location(1) velocity: vec2<f32>,
+}
+
+// the sky is infinitely far away, so only the camera rotation moves it on screen
+fn sky_motion_vector(direction: vec3<f32>) -> vec2<f32> {
+    // an orthographic view shows the same sky colour everywhere, directions have no w to divide by
+    if (sky.orthographic != 0u) {
+        return vec2<f32>(0.0);
+    }
+    let current_clip = sky.view_proj * vec4<f32>(direction, 0.0);
+    let previous_clip = sky.previous_view_proj * vec4<f32>(direction, 0.0);
+    // behind last frame's camera, or last frame was orthographic
+    if (previous_clip.w <= 0.0) {
+        return vec2<f32>(0.0);
+    }
+    let current = current_clip.xy / current_clip.w;
+    let previous = previous_clip.xy / previous_clip.w;
+    return (current - previous) * vec2<f32>(0.5, -0.5);
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> FragmentOutput {
+    // reverse-Z puts the far plane at infinity, unproject a point on the near plane instead
+    let near = sky.inverse_view_proj * vec4<f32>(in.ndc, 1.0, 1.0);
+    var direction: vec3<f32>;
+    if (sky.orthographic != 0u) {
+        // parallel rays, every pixel looks along the view's forward vector
+        let far = sky.inverse_view_proj * vec4<f32>(in.ndc, 0.0, 1.0);
+        direction = normalize(far.xyz / far.w - near.xyz / near.w);
+    } else {
+        direction = normalize(near.xyz / near.w - sky.camera_position.xyz);
+    }
+
+    var color: vec3<f32>;
+    if (sky.model == MODEL_GRADIENT) {
+        color = gradient(direction);
+    } else {
+        // the atmosphere goes black once the sun has set, the gradient carries dusk and night
+        let night = smoothstep(0.1, -0.1, normalize(sky.sun_direction).y);
+        color = max(atmosphere(direction), gradient(direction) * night);
+    }
+    var out: FragmentOutput;
+    out.color = vec4<f32>(color, 1.0);
+    out.velocity = sky_motion_vector(direction);
+    return out;
+}
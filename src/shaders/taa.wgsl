@@ -0,0 +1,79 @@
+// Blends the jittered scene into a reprojected history, drawn as one full-screen triangle
+
+struct TaaUniforms {
+    history_weight: f32,
+    history_valid: u32,
+    _padding1: f32,
+    _padding2: f32,
+}
+
+@group(0) @binding(0)
+var current_texture: texture_2d<f32>;
+
+@group(0) @binding(1)
+var velocity_texture: texture_2d<f32>;
+
+@group(0) @binding(2)
+var history_texture: texture_2d<f32>;
+
+@group(0) @binding(3)
+var history_sampler: sampler;
+
+@group(0) @binding(4)
+var<uniform> taa: TaaUniforms;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+fn luminance(color: vec3<f32>) -> f32 {
+    return dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let size = vec2<i32>(textureDimensions(current_texture));
+    let pixel = vec2<i32>(position.xy);
+    let current = textureLoad(current_texture, pixel, 0).rgb;
+
+    // colour range of the neighbourhood and the longest motion in it, so the
+    // edges of moving blades pick up their motion rather than the background's
+    var neighbourhood_min = current;
+    var neighbourhood_max = current;
+    var velocity = vec2<f32>(0.0);
+    for (var y = -1; y <= 1; y++) {
+        for (var x = -1; x <= 1; x++) {
+            let sample_pixel = clamp(pixel + vec2<i32>(x, y), vec2<i32>(0), size - 1);
+            let color = textureLoad(current_texture, sample_pixel, 0).rgb;
+            neighbourhood_min = min(neighbourhood_min, color);
+            neighbourhood_max = max(neighbourhood_max, color);
+
+            let sample_velocity = textureLoad(velocity_texture, sample_pixel, 0).xy;
+            if (dot(sample_velocity, sample_velocity) > dot(velocity, velocity)) {
+                velocity = sample_velocity;
+            }
+        }
+    }
+
+    let uv = position.xy / vec2<f32>(size);
+    let history_uv = uv - velocity;
+    let on_screen = all(history_uv >= vec2<f32>(0.0)) && all(history_uv <= vec2<f32>(1.0));
+    if (taa.history_valid == 0u || !on_screen) {
+        return vec4<f32>(current, 1.0);
+    }
+
+    // clamping to the neighbourhood rejects history that was disoccluded or changed
+    let history = clamp(
+        textureSampleLevel(history_texture, history_sampler, history_uv, 0.0).rgb,
+        neighbourhood_min,
+        neighbourhood_max,
+    );
+
+    // weighting by inverse luminance keeps bright specks from flickering
+    let current_weight = (1.0 - taa.history_weight) / (1.0 + luminance(current));
+    let history_weight = taa.history_weight / (1.0 + luminance(history));
+    let color = (current * current_weight + history * history_weight) / (current_weight + history_weight);
+    return vec4<f32>(color, 1.0);
+}
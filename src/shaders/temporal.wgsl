@@ -0,0 +1,23 @@
+// Unjittered camera matrices of this and the previous frame, prepended to the grass and
+// ground shaders to write motion vectors for TAA
+
+struct TemporalUniforms {
+    view_proj: mat4x4<f32>,
+    previous_view_proj: mat4x4<f32>,
+}
+
+@group(0) @binding(4)
+var<uniform> temporal: TemporalUniforms;
+
+struct FragmentOutput {
+    @location(0) color: vec4<f32>,
+    // screen space motion since the previous frame, in UV units
+    @location(1) velocity: vec2<f32>,
+}
+
+fn motion_vector(current_clip: vec4<f32>, previous_clip: vec4<f32>) -> vec2<f32> {
+    let current = current_clip.xy / current_clip.w;
+    let previous = previous_clip.xy / previous_clip.w;
+    return (current - previous) * vec2<f32>(0.5, -0.5);
+}
+
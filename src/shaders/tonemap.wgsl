@@ -0,0 +1,100 @@
+// Maps the HDR scene into display range, drawn as one full-screen triangle
+
+struct TonemapUniforms {
+    exposure: f32,
+    tonemapper: u32,
+    auto_exposure: u32,
+    key_value: f32,
+}
+
+struct Luminance {
+    average: f32,
+    _padding1: f32,
+    _padding2: f32,
+    _padding3: f32,
+}
+
+@group(0) @binding(0)
+var hdr_texture: texture_2d<f32>;
+
+@group(0) @binding(1)
+var<uniform> tonemap: TonemapUniforms;
+
+@group(0) @binding(2)
+var<uniform> luminance: Luminance;
+
+const TONEMAPPER_ACES: u32 = 0u;
+const TONEMAPPER_AGX: u32 = 1u;
+const TONEMAPPER_REINHARD: u32 = 2u;
+const TONEMAPPER_CLAMP: u32 = 3u;
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> @builtin(position) vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+// Narkowicz fit of the ACES filmic curve
+fn aces(x: vec3<f32>) -> vec3<f32> {
+    return saturate((x * (2.51 * x + 0.03)) / (x * (2.43 * x + 0.59) + 0.14));
+}
+
+fn agx_contrast(x: vec3<f32>) -> vec3<f32> {
+    let x2 = x * x;
+    let x4 = x2 * x2;
+    return 15.5 * x4 * x2 - 40.14 * x4 * x + 31.96 * x4 - 6.868 * x2 * x + 0.4298 * x2 + 0.1191 * x - 0.00232;
+}
+
+// Minimal AgX with the default look, returns linear colour
+fn agx(color: vec3<f32>) -> vec3<f32> {
+    let inset = mat3x3<f32>(
+        vec3<f32>(0.842479062253094, 0.0423282422610123, 0.0423756549057051),
+        vec3<f32>(0.0784335999999992, 0.878468636469772, 0.0784336),
+        vec3<f32>(0.0792237451477643, 0.0791661274605434, 0.879142973793104),
+    );
+    let outset = mat3x3<f32>(
+        vec3<f32>(1.19687900512017, -0.0528968517574562, -0.0529716355144438),
+        vec3<f32>(-0.0980208811401368, 1.15190312990417, -0.0980434501171241),
+        vec3<f32>(-0.0990297440797205, -0.0989611768448433, 1.15107367264116),
+    );
+    let min_ev = -12.47393;
+    let max_ev = 4.026069;
+
+    var x = inset * color;
+    x = clamp(log2(max(x, vec3<f32>(1e-10))), vec3<f32>(min_ev), vec3<f32>(max_ev));
+    x = (x - min_ev) / (max_ev - min_ev);
+    x = outset * agx_contrast(x);
+    return pow(saturate(x), vec3<f32>(2.2));
+}
+
+fn reinhard(x: vec3<f32>) -> vec3<f32> {
+    return x / (1.0 + x);
+}
+
+@fragment
+fn fs_main(@builtin(position) position: vec4<f32>) -> @location(0) vec4<f32> {
+    let hdr = textureLoad(hdr_texture, vec2<i32>(position.xy), 0).rgb;
+
+    var exposure = tonemap.exposure;
+    if (tonemap.auto_exposure != 0u) {
+        exposure *= tonemap.key_value / max(luminance.average, 1e-4);
+    }
+    let color = hdr * exposure;
+
+    var mapped: vec3<f32>;
+    switch tonemap.tonemapper {
+        case TONEMAPPER_AGX: {
+            mapped = agx(color);
+        }
+        case TONEMAPPER_REINHARD: {
+            mapped = reinhard(color);
+        }
+        case TONEMAPPER_CLAMP: {
+            mapped = saturate(color);
+        }
+        default: {
+            mapped = aces(color);
+        }
+    }
+    return vec4<f32>(mapped, 1.0);
+}
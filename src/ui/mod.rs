@@ -0,0 +1,74 @@
+pub mod panels;
+
+use std::path::PathBuf;
+
+use winit::event::{ElementState, WindowEvent};
+use winit::window::Window;
+
+use crate::renderer::Renderer;
+use crate::renderer::ui::UiFrame;
+use panels::Panels;
+
+/// egui overlay with the parameter panels, fed with the window events
+pub struct Ui {
+    context: egui::Context,
+    state: egui_winit::State,
+    panels: Panels,
+    pub visible: bool,
+}
+
+impl Ui {
+    /// `settings_path` is where the panels save and load the settings
+    pub fn new(window: &Window, settings_path: PathBuf) -> Self {
+        let context = egui::Context::default();
+        let state = egui_winit::State::new(
+            context.clone(),
+            egui::ViewportId::ROOT,
+            window,
+            Some(window.scale_factor() as f32),
+            window.theme(),
+            None,
+        );
+        Self {
+            context,
+            state,
+            panels: Panels::new(settings_path),
+            visible: false,
+        }
+    }
+
+    /// True if egui used the event and the scene should not react to it
+    ///
+    /// Releases and cursor moves are never claimed, so drags and held keys
+    /// started in the scene still end there.
+    pub fn on_window_event(&mut self, window: &Window, event: &WindowEvent) -> bool {
+        if !self.visible {
+            return false;
+        }
+        let consumed = self.state.on_window_event(window, event).consumed;
+        consumed
+            && match event {
+                WindowEvent::MouseInput { state, .. } => *state == ElementState::Pressed,
+                WindowEvent::KeyboardInput { event, .. } => event.state == ElementState::Pressed,
+                WindowEvent::MouseWheel { .. } => true,
+                _ => false,
+            }
+    }
+
+    /// Lays out the panels and hands them to the renderer for the next frame
+    pub fn prepare(&mut self, window: &Window, renderer: &mut Renderer) {
+        if !self.visible {
+            return;
+        }
+        let input = self.state.take_egui_input(window);
+        let output = self.context.run(input, |context| self.panels.show(context, renderer));
+        self.state.handle_platform_output(window, output.platform_output);
+
+        let pixels_per_point = output.pixels_per_point;
+        renderer.set_ui_frame(UiFrame {
+            primitives: self.context.tessellate(output.shapes, pixels_per_point),
+            textures_delta: output.textures_delta,
+            pixels_per_point,
+        });
+    }
+}
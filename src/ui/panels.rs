@@ -0,0 +1,254 @@
+use std::path::PathBuf;
+
+use crate::camera::controller::CameraMode;
+use crate::lighting::day_cycle::DayCycle;
+use crate::renderer::Renderer;
+use crate::renderer::debug_view::DebugView;
+use crate::renderer::profiler::GpuPass;
+use crate::renderer::sky::SkyModel;
+use crate::renderer::tonemap::Tonemapper;
+use crate::settings::Settings;
+use crate::wind::WindField;
+
+/// Side panel with one section per group of parameters, edits go straight to the renderer
+pub struct Panels {
+    settings_path: PathBuf,
+    /// Outcome of the last save or load
+    status: String,
+}
+
+impl Panels {
+    pub fn new(settings_path: PathBuf) -> Self {
+        Self {
+            settings_path,
+            status: String::new(),
+        }
+    }
+
+    pub fn show(&mut self, context: &egui::Context, renderer: &mut Renderer) {
+        egui::SidePanel::left("settings").default_width(300.0).show(context, |ui| {
+            egui::ScrollArea::vertical().show(ui, |ui| {
+                egui::CollapsingHeader::new("Wind")
+                    .default_open(true)
+                    .show(ui, |ui| wind_panel(ui, renderer));
+                egui::CollapsingHeader::new("Blades")
+                    .default_open(true)
+                    .show(ui, |ui| blade_panel(ui, renderer));
+                egui::CollapsingHeader::new("Lighting").show(ui, |ui| lighting_panel(ui, renderer));
+                egui::CollapsingHeader::new("Camera").show(ui, |ui| camera_panel(ui, renderer));
+                egui::CollapsingHeader::new("Performance")
+                    .default_open(true)
+                    .show(ui, |ui| performance_panel(ui, renderer));
+                ui.separator();
+                self.settings_file(ui, renderer);
+            });
+        });
+    }
+
+    fn settings_file(&mut self, ui: &mut egui::Ui, renderer: &mut Renderer) {
+        ui.label(format!("Settings file: {}", self.settings_path.display()));
+        ui.horizontal(|ui| {
+            if ui.button("Save").clicked() {
+                self.status = match Settings::capture(renderer).save(&self.settings_path) {
+                    Ok(()) => "Saved".to_string(),
+                    Err(err) => format!("Save failed: {}", err),
+                };
+                log::info!("{} {}", self.status, self.settings_path.display());
+            }
+            if ui.button("Load").clicked() {
+                self.status = match Settings::load(&self.settings_path) {
+                    Ok(settings) => {
+                        settings.apply(renderer);
+                        "Loaded".to_string()
+                    }
+                    Err(err) => format!("Load failed: {}", err),
+                };
+                log::info!("{} {}", self.status, self.settings_path.display());
+            }
+            ui.label(&self.status);
+        });
+    }
+}
+
+fn wind_panel(ui: &mut egui::Ui, renderer: &mut Renderer) {
+    ui.horizontal(|ui| {
+        if ui.button("Calm").clicked() {
+            *renderer.wind_mut() = WindField::calm();
+        }
+        if ui.button("Breeze").clicked() {
+            *renderer.wind_mut() = WindField::breeze();
+        }
+        if ui.button("Storm").clicked() {
+            *renderer.wind_mut() = WindField::storm();
+        }
+    });
+
+    let wind = renderer.wind_mut();
+    angle_slider(ui, &mut wind.direction, "Direction");
+    ui.add(egui::Slider::new(&mut wind.strength, 0.0..=2.0).text("Strength"));
+    ui.add(egui::Slider::new(&mut wind.gust_frequency, 0.0..=1.0).text("Gust frequency"));
+    ui.add(egui::Slider::new(&mut wind.gust_amplitude, 0.0..=2.0).text("Gust amplitude"));
+    ui.add(egui::Slider::new(&mut wind.turbulence_scale, 0.1..=4.0).text("Turbulence scale"));
+    ui.add(egui::Slider::new(&mut wind.turbulence_speed, 0.0..=10.0).text("Turbulence speed"));
+    ui.add(egui::Slider::new(&mut wind.turbulence_strength, 0.0..=1.0).text("Turbulence strength"));
+    for (i, layer) in wind.layers.iter_mut().enumerate() {
+        ui.label(format!("Layer {}", i + 1));
+        ui.add(egui::Slider::new(&mut layer.scale, 0.01..=0.5).logarithmic(true).text("Scale"));
+        ui.add(egui::Slider::new(&mut layer.speed, -5.0..=5.0).text("Speed"));
+        ui.add(egui::Slider::new(&mut layer.amplitude, 0.0..=1.5).text("Amplitude"));
+        angle_slider(ui, &mut layer.angle_offset, "Angle offset");
+    }
+}
+
+fn blade_panel(ui: &mut egui::Ui, renderer: &mut Renderer) {
+    let blade = renderer.blade_settings_mut();
+    ui.add(egui::Slider::new(&mut blade.height, 0.2..=3.0).text("Height"));
+    ui.add(egui::Slider::new(&mut blade.width, 0.2..=3.0).text("Width"));
+    ui.add(egui::Slider::new(&mut blade.bend, 0.0..=3.0).text("Bend"));
+    ui.add(egui::Slider::new(&mut blade.stiffness, 1.0..=200.0).logarithmic(true).text("Stiffness"));
+    ui.add(egui::Slider::new(&mut blade.damping, 0.0..=20.0).text("Damping"));
+    ui.add(egui::Slider::new(&mut blade.gravity, 0.0..=10.0).text("Gravity"));
+
+    let material = renderer.grass_material_mut();
+    ui.add(egui::Slider::new(&mut material.season, 0.0..=1.0).text("Season"));
+    ui.add(egui::Slider::new(&mut material.variation_strength, 0.0..=2.0).text("Colour variation"));
+    ui.add(egui::Slider::new(&mut material.dry_patch_coverage, 0.0..=1.0).text("Dry patches"));
+}
+
+fn lighting_panel(ui: &mut egui::Ui, renderer: &mut Renderer) {
+    let mut day_cycle_enabled = renderer.day_cycle_mut().is_some();
+    if ui.checkbox(&mut day_cycle_enabled, "Day cycle").changed() {
+        renderer.set_day_cycle(day_cycle_enabled.then(DayCycle::default));
+    }
+    if let Some(day_cycle) = renderer.day_cycle_mut() {
+        ui.add(egui::Slider::new(&mut day_cycle.time_of_day, 0.0..=24.0).text("Time of day"));
+        ui.add(egui::Slider::new(&mut day_cycle.speed, 0.0..=6000.0).logarithmic(true).text("Speed"));
+    } else {
+        // the day cycle owns the sun while it runs
+        let lighting = renderer.lighting_mut();
+        let direction = lighting.sun_direction.normalize_or(glam::Vec3::Y);
+        let mut azimuth = direction.z.atan2(direction.x);
+        let mut elevation = direction.y.clamp(-1.0, 1.0).asin();
+        let changed = angle_slider(ui, &mut azimuth, "Sun azimuth")
+            | ui.add(egui::Slider::new(&mut elevation, -0.2..=1.5).text("Sun elevation")).changed();
+        if changed {
+            lighting.sun_direction = glam::Vec3::new(
+                elevation.cos() * azimuth.cos(),
+                elevation.sin(),
+                elevation.cos() * azimuth.sin(),
+            );
+            lighting.sky_sun_direction = lighting.sun_direction;
+        }
+        ui.add(egui::Slider::new(&mut lighting.sun_intensity, 0.0..=4.0).text("Sun intensity"));
+        ui.add(egui::Slider::new(&mut lighting.ambient_intensity, 0.0..=2.0).text("Ambient"));
+    }
+
+    let lighting = renderer.lighting_mut();
+    ui.add(egui::Slider::new(&mut lighting.fog.density, 0.0..=0.05).text("Fog density"));
+    ui.add(egui::Slider::new(&mut lighting.fog.height_falloff, 0.0..=1.0).text("Fog falloff"));
+    ui.add(egui::Slider::new(&mut lighting.translucency.strength, 0.0..=8.0).text("Translucency"));
+
+    let sky = renderer.sky_settings_mut();
+    egui::ComboBox::from_label("Sky")
+        .selected_text(format!("{:?}", sky.model))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut sky.model, SkyModel::Atmosphere, "Atmosphere");
+            ui.selectable_value(&mut sky.model, SkyModel::Gradient, "Gradient");
+        });
+
+    let tonemap = renderer.tonemap_settings_mut();
+    egui::ComboBox::from_label("Tonemapper")
+        .selected_text(format!("{:?}", tonemap.tonemapper))
+        .show_ui(ui, |ui| {
+            ui.selectable_value(&mut tonemap.tonemapper, Tonemapper::Clamp, "Clamp");
+            ui.selectable_value(&mut tonemap.tonemapper, Tonemapper::Aces, "ACES");
+            ui.selectable_value(&mut tonemap.tonemapper, Tonemapper::AgX, "AgX");
+            ui.selectable_value(&mut tonemap.tonemapper, Tonemapper::Reinhard, "Reinhard");
+        });
+    ui.add(egui::Slider::new(&mut tonemap.exposure, 0.125..=8.0).logarithmic(true).text("Exposure"));
+    ui.checkbox(&mut tonemap.auto_exposure, "Auto-exposure");
+}
+
+fn camera_panel(ui: &mut egui::Ui, renderer: &mut Renderer) {
+    let controller = renderer.camera_controller_mut();
+    let mut mode = controller.mode;
+    ui.horizontal(|ui| {
+        ui.radio_value(&mut mode, CameraMode::Orbit, "Orbit");
+        ui.radio_value(&mut mode, CameraMode::Fly, "Fly");
+    });
+    if mode != controller.mode {
+        controller.set_mode(mode);
+    }
+    ui.add(egui::Slider::new(&mut controller.fly_speed, 1.0..=50.0).text("Fly speed"));
+    ui.add(egui::Slider::new(&mut controller.rotation_speed, 0.001..=0.02).text("Rotation speed"));
+    ui.add(egui::Slider::new(&mut controller.zoom_speed, 0.5..=10.0).text("Zoom speed"));
+    ui.add(egui::Slider::new(&mut controller.smoothing, 1.0..=30.0).text("Smoothing"));
+    ui.checkbox(&mut controller.ground_following, "Follow ground");
+
+    let camera = renderer.camera_mut();
+    let mut fovy = camera.fovy.to_degrees();
+    if ui.add(egui::Slider::new(&mut fovy, 20.0..=100.0).text("Field of view")).changed() {
+        camera.fovy = fovy.to_radians();
+    }
+
+    let mut top_down = renderer.is_top_down();
+    if ui.checkbox(&mut top_down, "Top-down").changed() {
+        renderer.set_top_down(top_down);
+    }
+    ui.checkbox(&mut renderer.minimap_settings_mut().enabled, "Minimap");
+    ui.checkbox(&mut renderer.taa_settings_mut().enabled, "TAA");
+    let mut wireframe = renderer.is_wireframe();
+    if ui.checkbox(&mut wireframe, "Wireframe").changed() {
+        renderer.set_wireframe(wireframe);
+    }
+    ui.checkbox(&mut renderer.debug_line_settings_mut().enabled, "Debug lines");
+
+    let debug_view = renderer.debug_view_mut();
+    egui::ComboBox::from_label("Debug view")
+        .selected_text(format!("{:?}", debug_view))
+        .show_ui(ui, |ui| {
+            for view in DebugView::ALL {
+                ui.selectable_value(debug_view, view, format!("{:?}", view));
+            }
+        });
+}
+
+fn performance_panel(ui: &mut egui::Ui, renderer: &mut Renderer) {
+    let stats = renderer.frame_stats();
+    ui.label(format!(
+        "{:.0} fps, {:.2} ms average, {:.2} ms worst",
+        stats.frames_per_second(),
+        stats.average_frame_time() * 1000.0,
+        stats.max_frame_time() * 1000.0,
+    ));
+    ui.label(format!("Simulation steps: {}", stats.simulation_steps));
+    ui.label(format!("Blades: {}", renderer.blade_count()));
+    let size = renderer.size();
+    ui.label(format!("Resolution: {}x{}, MSAA {}x", size.width, size.height, renderer.sample_count()));
+    ui.label(format!("Adapter: {}", renderer.adapter_name()));
+
+    match renderer.gpu_profiler() {
+        Some(gpu_profiler) => {
+            for pass in GpuPass::ALL {
+                if let Some(milliseconds) = gpu_profiler.average_milliseconds(pass) {
+                    ui.label(format!("GPU {:?}: {:.3} ms", pass, milliseconds));
+                }
+            }
+        }
+        None => {
+            ui.label("GPU timings need TIMESTAMP_QUERY");
+        }
+    }
+}
+
+/// Slider over a full turn, shown in degrees and stored in radians
+fn angle_slider(ui: &mut egui::Ui, radians: &mut f32, text: &str) -> bool {
+    let mut degrees = radians.to_degrees();
+    let changed = ui
+        .add(egui::Slider::new(&mut degrees, -180.0..=180.0).suffix("°").text(text))
+        .changed();
+    if changed {
+        *radians = degrees.to_radians();
+    }
+    changed
+}
@@ -0,0 +1,145 @@
+use glam::Vec3;
+
+/// Maximum number of emitters uploaded to the GPU at once
+pub const MAX_WIND_EMITTERS: usize = 64;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum WindEmitterKind {
+    /// Pushes blades away from the centre, e.g. explosions or rotor downwash
+    Radial,
+    /// Blows along `direction` (radians) within a cone of half angle `spread`
+    Directional { direction: f32, spread: f32 },
+    /// Swirls blades around the centre, counter-clockwise for positive strength
+    Vortex,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct WindEmitter {
+    pub kind: WindEmitterKind,
+    pub position: Vec3,
+    /// Distance at which the emitter has no effect anymore
+    pub radius: f32,
+    /// Lean in radians the emitter causes at its centre
+    pub strength: f32,
+    /// Seconds until the emitter is removed, `f32::INFINITY` keeps it alive
+    pub lifetime: f32,
+    age: f32,
+}
+
+impl WindEmitter {
+    pub fn new(kind: WindEmitterKind, position: Vec3, radius: f32, strength: f32, lifetime: f32) -> Self {
+        Self {
+            kind,
+            position,
+            radius,
+            strength,
+            lifetime,
+            age: 0.0,
+        }
+    }
+
+    pub fn radial(position: Vec3, radius: f32, strength: f32, lifetime: f32) -> Self {
+        Self::new(WindEmitterKind::Radial, position, radius, strength, lifetime)
+    }
+
+    pub fn directional(
+        position: Vec3,
+        direction: f32,
+        spread: f32,
+        radius: f32,
+        strength: f32,
+        lifetime: f32,
+    ) -> Self {
+        Self::new(
+            WindEmitterKind::Directional { direction, spread },
+            position,
+            radius,
+            strength,
+            lifetime,
+        )
+    }
+
+    pub fn vortex(position: Vec3, radius: f32, strength: f32, lifetime: f32) -> Self {
+        Self::new(WindEmitterKind::Vortex, position, radius, strength, lifetime)
+    }
+
+    /// Strength multiplier, fades linearly to zero over the lifetime
+    fn fade(&self) -> f32 {
+        if self.lifetime.is_finite() {
+            (1.0 - self.age / self.lifetime).max(0.0)
+        } else {
+            1.0
+        }
+    }
+
+    fn to_gpu(self) -> GpuWindEmitter {
+        let (kind, direction, cone_cos) = match self.kind {
+            WindEmitterKind::Radial => (0, [0.0, 0.0], -1.0),
+            WindEmitterKind::Directional { direction, spread } => {
+                (1, [direction.cos(), direction.sin()], spread.cos())
+            }
+            WindEmitterKind::Vortex => (2, [0.0, 0.0], -1.0),
+        };
+
+        GpuWindEmitter {
+            position: [self.position.x, self.position.z],
+            radius: self.radius,
+            strength: self.strength * self.fade(),
+            direction,
+            cone_cos,
+            kind,
+        }
+    }
+}
+
+/// Emitter as seen by compute.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct GpuWindEmitter {
+    pub position: [f32; 2],
+    pub radius: f32,
+    pub strength: f32,
+    pub direction: [f32; 2],
+    pub cone_cos: f32,
+    pub kind: u32,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct EmitterId(u64);
+
+/// Set of live emitters, aged every frame and summed into the wind in the compute pass
+#[derive(Default)]
+pub struct WindEmitters {
+    emitters: Vec<(EmitterId, WindEmitter)>,
+    next_id: u64,
+}
+
+impl WindEmitters {
+    pub fn spawn(&mut self, emitter: WindEmitter) -> EmitterId {
+        let id = EmitterId(self.next_id);
+        self.next_id += 1;
+        self.emitters.push((id, emitter));
+        id
+    }
+
+    pub fn remove(&mut self, id: EmitterId) {
+        self.emitters.retain(|(emitter_id, _)| *emitter_id != id);
+    }
+
+    /// Ages all emitters and drops the expired ones
+    pub fn update(&mut self, delta_time: f32) {
+        for (_, emitter) in &mut self.emitters {
+            emitter.age += delta_time;
+        }
+        self.emitters.retain(|(_, emitter)| emitter.age < emitter.lifetime);
+    }
+
+    /// Emitters to upload, capped to `MAX_WIND_EMITTERS` (oldest first)
+    pub fn gpu_data(&self) -> Vec<GpuWindEmitter> {
+        self.emitters
+            .iter()
+            .take(MAX_WIND_EMITTERS)
+            .map(|(_, emitter)| emitter.to_gpu())
+            .collect()
+    }
+}
@@ -0,0 +1,72 @@
+use std::path::Path;
+
+/// How the flow map combines with the base wind direction
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum FlowMapMode {
+    /// No flow map, blades follow the global wind direction
+    #[default]
+    Disabled,
+    /// Painted vectors replace the wind direction, their length scales the strength
+    Override,
+    /// Painted vectors are added to the wind direction
+    Bias,
+}
+
+impl FlowMapMode {
+    pub fn as_gpu(self) -> u32 {
+        match self {
+            FlowMapMode::Disabled => 0,
+            FlowMapMode::Override => 1,
+            FlowMapMode::Bias => 2,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct FlowMapSettings {
+    pub mode: FlowMapMode,
+    /// Blend between the global wind (0) and the flow map (1)
+    pub strength: f32,
+    /// Scroll speed in UV units per second
+    pub scroll: [f32; 2],
+}
+
+impl Default for FlowMapSettings {
+    fn default() -> Self {
+        Self {
+            mode: FlowMapMode::Disabled,
+            strength: 1.0,
+            scroll: [0.0, 0.0],
+        }
+    }
+}
+
+/// Flow map pixels, red and green hold the x/z direction remapped to 0..255
+pub struct FlowMapImage {
+    pub width: u32,
+    pub height: u32,
+    pub data: Vec<u8>,
+}
+
+impl FlowMapImage {
+    /// Loads an image covering the whole field, the top-left pixel maps to -x/-z
+    pub fn load(path: &Path) -> Result<Self, image::ImageError> {
+        let image = image::open(path)?.to_rgba8();
+        let (width, height) = image.dimensions();
+        let data = image
+            .pixels()
+            .flat_map(|pixel| [pixel[0], pixel[1]])
+            .collect();
+
+        Ok(Self { width, height, data })
+    }
+
+    /// 1x1 map with a zero vector, bound when no flow map is loaded
+    pub fn neutral() -> Self {
+        Self {
+            width: 1,
+            height: 1,
+            data: vec![128, 128],
+        }
+    }
+}
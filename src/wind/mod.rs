@@ -0,0 +1,180 @@
+pub mod emitter;
+pub mod flow_map;
+
+use crate::config::{FIELD_HALF_SIZE, SIMULATION_TIMESTEP, WIND_ANGLE, WIND_STRENGTH};
+use crate::grass::blade::BladeSettings;
+use flow_map::FlowMapSettings;
+
+/// Maximum number of noise layers the compute shader evaluates
+pub const MAX_WIND_LAYERS: usize = 4;
+
+/// A scrolling noise layer, laid out to match `WindLayer` in compute.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct WindLayer {
+    /// Spatial frequency of the noise (smaller = wider waves)
+    pub scale: f32,
+    /// Scroll speed along the layer direction, negative scrolls against the wind
+    pub speed: f32,
+    pub amplitude: f32,
+    /// Direction relative to the base wind direction in radians
+    pub angle_offset: f32,
+}
+
+impl WindLayer {
+    pub fn new(scale: f32, speed: f32, amplitude: f32) -> Self {
+        Self {
+            scale,
+            speed,
+            amplitude,
+            angle_offset: 0.0,
+        }
+    }
+}
+
+/// Describes the wind over the whole field
+#[derive(Clone, Debug)]
+pub struct WindField {
+    /// Base wind direction in radians
+    pub direction: f32,
+    pub strength: f32,
+    /// How often gusts sweep across the field
+    pub gust_frequency: f32,
+    /// Extra strength at the peak of a gust, relative to the base strength
+    pub gust_amplitude: f32,
+    /// Spatial frequency of the per-blade turbulence
+    pub turbulence_scale: f32,
+    pub turbulence_speed: f32,
+    pub turbulence_strength: f32,
+    pub layers: Vec<WindLayer>,
+}
+
+impl Default for WindField {
+    fn default() -> Self {
+        Self::breeze()
+    }
+}
+
+impl WindField {
+    /// Barely moving grass
+    pub fn calm() -> Self {
+        Self {
+            direction: WIND_ANGLE,
+            strength: 0.2,
+            gust_frequency: 0.05,
+            gust_amplitude: 0.0,
+            turbulence_scale: 1.0,
+            turbulence_speed: 1.0,
+            turbulence_strength: 0.05,
+            layers: vec![WindLayer::new(0.03, 0.4, 0.8)],
+        }
+    }
+
+    /// Steady breeze with a single large wave, the default look
+    pub fn breeze() -> Self {
+        Self {
+            direction: WIND_ANGLE,
+            strength: WIND_STRENGTH,
+            gust_frequency: 0.1,
+            gust_amplitude: 0.0,
+            turbulence_scale: 1.0,
+            turbulence_speed: 2.5,
+            turbulence_strength: 0.125,
+            layers: vec![WindLayer::new(0.05, 1.0, 0.8)],
+        }
+    }
+
+    /// Strong gusty wind with several crossing waves
+    pub fn storm() -> Self {
+        Self {
+            direction: WIND_ANGLE,
+            strength: 1.2,
+            gust_frequency: 0.3,
+            gust_amplitude: 0.8,
+            turbulence_scale: 1.5,
+            turbulence_speed: 5.0,
+            turbulence_strength: 0.25,
+            layers: vec![
+                WindLayer::new(0.05, 3.0, 0.8),
+                WindLayer::new(0.08, -0.8, 0.5),
+                WindLayer {
+                    angle_offset: 0.6,
+                    ..WindLayer::new(0.2, 4.0, 0.3)
+                },
+            ],
+        }
+    }
+}
+
+/// Uniform block shared by the compute and render passes, matches `WindUniforms` in compute.wgsl
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct WindUniforms {
+    pub strength: f32,
+    pub time: f32,
+    pub angle: f32,
+    pub instance_count: f32,
+    pub blade_base_height: f32,
+    pub delta_time: f32,
+    pub stiffness: f32,
+    pub damping: f32,
+    pub gravity: f32,
+    pub gust_frequency: f32,
+    pub gust_amplitude: f32,
+    pub turbulence_scale: f32,
+    pub turbulence_speed: f32,
+    pub turbulence_strength: f32,
+    pub layer_count: u32,
+    pub emitter_count: u32,
+    pub flow_map_scroll: [f32; 2],
+    pub flow_map_mode: u32,
+    pub flow_map_strength: f32,
+    pub field_half_size: f32,
+    pub blade_width: f32,
+    pub blade_bend: f32,
+    pub _padding: f32,
+    pub layers: [WindLayer; MAX_WIND_LAYERS],
+}
+
+impl WindUniforms {
+    /// Layers beyond `MAX_WIND_LAYERS` are ignored
+    pub fn new(
+        field: &WindField,
+        blade: &BladeSettings,
+        time: f32,
+        instance_count: u32,
+        emitter_count: u32,
+        flow_map: &FlowMapSettings,
+    ) -> Self {
+        let mut layers = [WindLayer::new(0.0, 0.0, 0.0); MAX_WIND_LAYERS];
+        let layer_count = field.layers.len().min(MAX_WIND_LAYERS);
+        layers[..layer_count].copy_from_slice(&field.layers[..layer_count]);
+
+        Self {
+            strength: field.strength,
+            time,
+            angle: field.direction,
+            instance_count: instance_count as f32,
+            blade_base_height: blade.height,
+            delta_time: SIMULATION_TIMESTEP,
+            stiffness: blade.stiffness,
+            damping: blade.damping,
+            gravity: blade.gravity,
+            gust_frequency: field.gust_frequency,
+            gust_amplitude: field.gust_amplitude,
+            turbulence_scale: field.turbulence_scale,
+            turbulence_speed: field.turbulence_speed,
+            turbulence_strength: field.turbulence_strength,
+            layer_count: layer_count as u32,
+            emitter_count,
+            flow_map_scroll: flow_map.scroll,
+            flow_map_mode: flow_map.mode.as_gpu(),
+            flow_map_strength: flow_map.strength,
+            field_half_size: FIELD_HALF_SIZE,
+            blade_width: blade.width,
+            blade_bend: blade.bend,
+            _padding: 0.0,
+            layers,
+        }
+    }
+}